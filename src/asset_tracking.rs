@@ -14,6 +14,17 @@ pub trait LoadResource {
     /// have been loaded, it will be inserted as a resource. This ensures that the resource only
     /// exists when the assets are ready.
     fn load_resource<T: Resource + Asset + Clone + FromWorld>(&mut self) -> &mut Self;
+
+    /// Like [`LoadResource::load_resource`], but loads the [`Asset`] from a file
+    /// using the [`AssetLoader`] registered for it.
+    fn load_resource_from_path<T: Resource + Asset + Clone>(
+        &mut self,
+        path: &'static str,
+    ) -> &mut Self;
+
+    /// Replaces the [`Resource`] whenever its [`Asset`] changes on disk. This only has
+    /// an effect on native dev builds, where the file watcher is enabled.
+    fn hot_reload_resource<T: Resource + Asset + Clone>(&mut self) -> &mut Self;
 }
 
 impl LoadResource for App {
@@ -23,17 +34,54 @@ impl LoadResource for App {
         let value = T::from_world(world);
         let assets = world.resource::<AssetServer>();
         let handle = assets.add(value);
-        let mut handles = world.resource_mut::<ResourceHandles>();
-        handles
-            .waiting
-            .push_back((handle.untyped(), |world, handle| {
-                let assets = world.resource::<Assets<T>>();
-                if let Some(value) = assets.get(handle.id().typed::<T>()) {
-                    world.insert_resource(value.clone());
-                }
-            }));
+        wait_for_resource(world, handle);
         self
     }
+
+    fn load_resource_from_path<T: Resource + Asset + Clone>(
+        &mut self,
+        path: &'static str,
+    ) -> &mut Self {
+        let world = self.world_mut();
+        let assets = world.resource::<AssetServer>();
+        let handle = assets.load::<T>(path);
+        wait_for_resource(world, handle);
+        self
+    }
+
+    fn hot_reload_resource<T: Resource + Asset + Clone>(&mut self) -> &mut Self {
+        #[cfg(feature = "dev_native")]
+        self.add_systems(PreUpdate, reload_resource::<T>);
+        self
+    }
+}
+
+#[cfg(feature = "dev_native")]
+fn reload_resource<T: Resource + Asset + Clone>(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<T>>,
+    assets: Res<Assets<T>>,
+) {
+    for event in events.read() {
+        if let AssetEvent::Modified { id } = event
+            && let Some(value) = assets.get(*id)
+        {
+            info!("Reloaded resource {}", std::any::type_name::<T>());
+            commands.insert_resource(value.clone());
+        }
+    }
+}
+
+fn wait_for_resource<T: Resource + Asset + Clone>(world: &mut World, handle: Handle<T>) {
+    let mut handles = world.resource_mut::<ResourceHandles>();
+    handles
+        .waiting
+        .push_back((handle.untyped(), |world, handle| {
+            let assets = world.resource::<Assets<T>>();
+            if let Some(value) = assets.get(handle.id().typed::<T>()) {
+                world.insert_resource(value.clone());
+            }
+        }));
 }
 
 /// A function that inserts a loaded resource.
@@ -9,9 +9,6 @@ pub fn plugin(app: &mut App) {
 pub struct Assets {
     pub player: Handle<Image>,
     pub enemy: Handle<Image>,
-    pub up_speed: Handle<Image>,
-    pub up_explosion: Handle<Image>,
-    pub up_coin: Handle<Image>,
     pub noise: Handle<Image>,
     pub circle: Handle<Image>,
     pub square: Handle<Image>,
@@ -26,9 +23,6 @@ impl FromWorld for Assets {
         Self {
             player: server.load("images/player.png"),
             enemy: server.load("images/enemy.png"),
-            up_speed: server.load("images/speed.png"),
-            up_explosion: server.load("images/explosion.png"),
-            up_coin: server.load("images/coin.png"),
             noise: server.load("images/noise.png"),
             circle: server.load("images/circle.png"),
             square: server.load("images/square.png"),
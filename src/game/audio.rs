@@ -0,0 +1,283 @@
+use crate::AppSystems;
+use crate::asset_tracking::LoadResource;
+use crate::game::enemy::{Awake, Awaking, Behaviour, Enemy, EnemyKilled};
+use crate::game::screens::Screen;
+use crate::game::state_machine::Transitioned;
+use crate::game::storage;
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Key of the audio settings in the local storage.
+const STORAGE_KEY: &str = "audio";
+
+/// Maximum number of sounds of the same kind started within a single frame.
+/// An explosion can easily kill a hundred enemies at once.
+const MAX_SOUNDS_PER_FRAME: usize = 4;
+
+/// Number of awake enemies at which the music reaches full intensity.
+const MUSIC_FULL_INTENSITY: f32 = 96.0;
+
+/// Distance in world units at which sounds have lost most of their volume.
+pub const SPATIAL_SCALE: f32 = 256.0;
+
+pub fn plugin(app: &mut App) {
+    app.load_resource::<SoundAssets>();
+    app.add_event::<PlaySound>();
+
+    app.insert_resource(storage::load::<AudioSettings>(STORAGE_KEY).unwrap_or_default());
+    app.init_resource::<MusicIntensity>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        spawn_music.run_if(resource_exists::<SoundAssets>),
+    );
+
+    app.add_systems(
+        Update,
+        change_volume
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::RecordInput),
+    );
+
+    app.add_systems(
+        Update,
+        (
+            play_sounds_for_waking_enemies,
+            play_sounds_for_killed_enemies,
+            play_sounds,
+            update_music,
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay).and(resource_exists::<SoundAssets>))
+            .in_set(AppSystems::Update),
+    );
+}
+
+#[derive(Clone, Resource, Asset, TypePath)]
+pub struct SoundAssets {
+    pub wake: Handle<AudioSource>,
+    pub kill: Handle<AudioSource>,
+    pub explosion: Handle<AudioSource>,
+    pub pickup: Handle<AudioSource>,
+    pub music_base: Handle<AudioSource>,
+    pub music_intense: Handle<AudioSource>,
+}
+
+impl FromWorld for SoundAssets {
+    fn from_world(world: &mut World) -> Self {
+        let server = world.resource_mut::<AssetServer>();
+
+        Self {
+            wake: server.load("audio/wake.wav"),
+            kill: server.load("audio/kill.wav"),
+            explosion: server.load("audio/explosion.wav"),
+            pickup: server.load("audio/pickup.wav"),
+            music_base: server.load("audio/music_base.wav"),
+            music_intense: server.load("audio/music_intense.wav"),
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub sfx: f32,
+    pub music: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            sfx: 1.0,
+            music: 0.5,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    fn sfx_volume(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master * self.sfx
+        }
+    }
+
+    fn music_volume(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master * self.music
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Sound {
+    Wake,
+    Kill,
+    Explosion,
+    Pickup,
+}
+
+/// Plays a sound effect at the given position in the world.
+#[derive(Event)]
+pub struct PlaySound {
+    pub sound: Sound,
+    pub position: Vec2,
+}
+
+#[derive(Component)]
+enum MusicLayer {
+    /// Always playing.
+    Base,
+    /// Faded in as more enemies are awake.
+    Intense,
+}
+
+/// Smoothed intensity of the music between zero and one.
+#[derive(Resource, Default)]
+struct MusicIntensity(f32);
+
+fn spawn_music(
+    mut commands: Commands,
+    sounds: Res<SoundAssets>,
+    mut intensity: ResMut<MusicIntensity>,
+) {
+    intensity.0 = 0.0;
+
+    // both layers have the same length and are started in the same frame,
+    // so they stay in sync while looping.
+    for (layer, source) in [
+        (MusicLayer::Base, sounds.music_base.clone()),
+        (MusicLayer::Intense, sounds.music_intense.clone()),
+    ] {
+        commands.spawn((
+            Name::new("Music"),
+            StateScoped(Screen::Gameplay),
+            layer,
+            AudioPlayer::new(source),
+            PlaybackSettings::LOOP.with_volume(Volume::SILENT),
+        ));
+    }
+}
+
+fn play_sounds_for_waking_enemies(
+    mut transitions: EventReader<Transitioned<Behaviour>>,
+    enemies: Query<&Transform, With<Enemy>>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    // read all events, so the excess is dropped instead of played later
+    let waking: Vec<_> = transitions
+        .read()
+        .filter(|event| event.entered::<Awaking>())
+        .filter_map(|event| enemies.get(event.entity).ok())
+        .collect();
+
+    for transform in waking.into_iter().take(MAX_SOUNDS_PER_FRAME) {
+        sounds.write(PlaySound {
+            sound: Sound::Wake,
+            position: transform.translation.xy(),
+        });
+    }
+}
+
+fn play_sounds_for_killed_enemies(
+    mut killed: EventReader<EnemyKilled>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    let killed: Vec<_> = killed.read().collect();
+
+    for event in killed.into_iter().take(MAX_SOUNDS_PER_FRAME) {
+        sounds.write(PlaySound {
+            sound: Sound::Kill,
+            position: event.position,
+        });
+    }
+}
+
+fn play_sounds(
+    mut commands: Commands,
+    mut events: EventReader<PlaySound>,
+    settings: Res<AudioSettings>,
+    sounds: Res<SoundAssets>,
+) {
+    let mut started = Vec::new();
+
+    for event in events.read() {
+        let count = started.iter().filter(|s| **s == event.sound).count();
+        if count >= MAX_SOUNDS_PER_FRAME {
+            continue;
+        }
+
+        started.push(event.sound);
+
+        let (source, volume) = match event.sound {
+            Sound::Wake => (sounds.wake.clone(), 0.5),
+            Sound::Kill => (sounds.kill.clone(), 0.6),
+            Sound::Explosion => (sounds.explosion.clone(), 1.0),
+            Sound::Pickup => (sounds.pickup.clone(), 0.7),
+        };
+
+        commands.spawn((
+            Name::new("Sound"),
+            StateScoped(Screen::Gameplay),
+            AudioPlayer::new(source),
+            PlaybackSettings::DESPAWN
+                .with_spatial(true)
+                .with_volume(Volume::Linear(volume * settings.sfx_volume())),
+            Transform::from_translation(event.position.extend(0.0)),
+        ));
+    }
+}
+
+fn update_music(
+    time: Res<Time<Real>>,
+    settings: Res<AudioSettings>,
+    mut intensity: ResMut<MusicIntensity>,
+    enemies: Query<(), (With<Enemy>, With<Awake>)>,
+    mut layers: Query<(&MusicLayer, &mut AudioSink)>,
+) {
+    let target = (enemies.iter().count() as f32 / MUSIC_FULL_INTENSITY).min(1.0);
+    intensity.0.smooth_nudge(&target, 1.0, time.delta_secs());
+
+    for (layer, mut sink) in &mut layers {
+        let volume = match layer {
+            MusicLayer::Base => 1.0 - 0.3 * intensity.0,
+            MusicLayer::Intense => intensity.0,
+        };
+
+        sink.set_volume(Volume::Linear(volume * settings.music_volume()));
+    }
+}
+
+fn change_volume(keys: Res<ButtonInput<KeyCode>>, mut settings: ResMut<AudioSettings>) {
+    let mut master = settings.master;
+
+    if keys.any_just_pressed([KeyCode::Minus, KeyCode::NumpadSubtract]) {
+        master -= 0.1;
+    }
+
+    if keys.any_just_pressed([KeyCode::Equal, KeyCode::NumpadAdd]) {
+        master += 0.1;
+    }
+
+    let master = master.clamp(0.0, 1.0);
+    if master != settings.master {
+        settings.master = master;
+        settings.muted = false;
+    }
+
+    if keys.just_pressed(KeyCode::Digit0) {
+        settings.muted = !settings.muted;
+    }
+
+    // the settings were just loaded when the resource was added
+    if settings.is_changed() && !settings.is_added() {
+        storage::save(STORAGE_KEY, &*settings);
+    }
+}
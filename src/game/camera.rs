@@ -0,0 +1,243 @@
+use crate::AppSystems;
+use crate::game::VIEW_RADIUS;
+use crate::game::cursor::MainCamera;
+use crate::game::highscore::HighscoreState;
+use crate::game::player::Player;
+use crate::game::screens::Screen;
+use avian2d::prelude::LinearVelocity;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use fastnoise_lite::FastNoiseLite;
+
+/// Time the camera roughly needs to catch up with the player.
+const FOLLOW_SMOOTH_TIME: f32 = 0.25;
+
+/// How far ahead (in seconds of movement) the camera looks.
+const LOOK_AHEAD_SECS: f32 = 0.5;
+
+/// Area the `AutoMin` scaling mode guarantees at a zoom of one.
+pub const MIN_VIEW_WIDTH: f32 = 512.0;
+pub const MIN_VIEW_HEIGHT: f32 = 768.0;
+
+/// Windows more elongated than this are zoomed in, so they do not show more of the world.
+const MAX_ASPECT_RATIO: f32 = 21.0 / 9.0;
+
+/// Zoom limits, relative to the area the `AutoMin` scaling mode guarantees.
+const ZOOM_MIN: f32 = 0.6;
+const ZOOM_MAX: f32 = 1.6;
+
+// the largest view, a landscape window at the widest aspect ratio and zoom,
+// must stay within the radius gameplay treats as visible
+const _: () = {
+    let half_width = MIN_VIEW_HEIGHT * MAX_ASPECT_RATIO * ZOOM_MAX / 2.0;
+    let half_height = MIN_VIEW_HEIGHT * ZOOM_MAX / 2.0;
+    assert!(half_width * half_width + half_height * half_height <= VIEW_RADIUS * VIEW_RADIUS);
+};
+
+/// Maximum offset and rotation of the camera at full trauma.
+const SHAKE_MAX_OFFSET: f32 = 24.0;
+const SHAKE_MAX_ANGLE: f32 = 0.05;
+
+/// Trauma lost per second.
+const TRAUMA_DECAY: f32 = 1.2;
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<ShakeCamera>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_camera);
+
+    app.add_systems(
+        Update,
+        (zoom_mouse_wheel, zoom_pinch)
+            // the mouse wheel scrolls the highscore overlay instead
+            .run_if(in_state(Screen::Gameplay).and(in_state(HighscoreState::Closed)))
+            .in_set(AppSystems::RecordInput),
+    );
+
+    app.add_systems(
+        PostUpdate,
+        (add_trauma, camera_follow_player, apply_zoom)
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update)
+            .before(TransformSystem::TransformPropagate),
+    );
+}
+
+#[derive(Component)]
+pub struct CameraController {
+    /// Position the camera looks at, without any shake applied.
+    focus: Vec2,
+    velocity: Vec2,
+    /// Current and requested zoom, larger values show more of the world.
+    zoom: f32,
+    target_zoom: f32,
+    /// Amount of screen shake, between zero and one.
+    trauma: f32,
+    noise: FastNoiseLite,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        let mut noise = FastNoiseLite::new();
+        noise.frequency = 15.0;
+
+        Self {
+            focus: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+            zoom: 1.0,
+            target_zoom: 1.0,
+            trauma: 0.0,
+            noise,
+        }
+    }
+}
+
+/// Adds trauma to the camera which makes the screen shake.
+#[derive(Event)]
+pub struct ShakeCamera {
+    pub trauma: f32,
+}
+
+fn reset_camera(mut camera: Single<(&mut Transform, &mut CameraController)>) {
+    let (transform, controller) = &mut *camera;
+
+    controller.focus = Vec2::ZERO;
+    controller.velocity = Vec2::ZERO;
+    controller.trauma = 0.0;
+    controller.zoom = 1.0;
+    controller.target_zoom = 1.0;
+
+    transform.translation.x = 0.0;
+    transform.translation.y = 0.0;
+    transform.rotation = Quat::IDENTITY;
+}
+
+fn add_trauma(mut events: EventReader<ShakeCamera>, mut controller: Single<&mut CameraController>) {
+    for event in events.read() {
+        controller.trauma = (controller.trauma + event.trauma).min(1.0);
+    }
+}
+
+/// Critically damped spring towards the target, similar to unitys `SmoothDamp`.
+fn smooth_damp(
+    current: Vec2,
+    target: Vec2,
+    velocity: &mut Vec2,
+    smooth_time: f32,
+    dt: f32,
+) -> Vec2 {
+    let omega = 2.0 / smooth_time;
+    let x = omega * dt;
+    let exp = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+
+    let change = current - target;
+    let temp = (*velocity + omega * change) * dt;
+    *velocity = (*velocity - omega * temp) * exp;
+
+    target + (change + temp) * exp
+}
+
+fn camera_follow_player(
+    time: Res<Time<Real>>,
+    mut camera: Single<(&mut Transform, &mut CameraController), With<MainCamera>>,
+    player: Single<(&Transform, &LinearVelocity), (With<Player>, Without<MainCamera>)>,
+) {
+    let (transform, controller) = &mut *camera;
+    let (player_transform, player_velocity) = *player;
+
+    let dt = time.delta_secs();
+
+    // look a little into the direction the player is moving to
+    let target = player_transform.translation.xy() + player_velocity.0 * LOOK_AHEAD_SECS;
+
+    let mut velocity = controller.velocity;
+    controller.focus = smooth_damp(
+        controller.focus,
+        target,
+        &mut velocity,
+        FOLLOW_SMOOTH_TIME,
+        dt,
+    );
+    controller.velocity = velocity;
+
+    // shake the camera based on the current trauma
+    controller.trauma = (controller.trauma - TRAUMA_DECAY * dt).max(0.0);
+    let shake = controller.trauma * controller.trauma;
+
+    let t = time.elapsed_secs();
+    let offset = vec2(
+        controller.noise.get_noise_2d(t, 0.0),
+        controller.noise.get_noise_2d(0.0, t),
+    ) * SHAKE_MAX_OFFSET
+        * shake;
+
+    let angle = controller.noise.get_noise_2d(t, t) * SHAKE_MAX_ANGLE * shake;
+
+    let position = controller.focus + offset;
+    transform.translation.x = position.x;
+    transform.translation.y = position.y;
+    transform.rotation = Quat::from_rotation_z(angle);
+}
+
+fn zoom_mouse_wheel(
+    mut events: EventReader<MouseWheel>,
+    mut controller: Single<&mut CameraController>,
+) {
+    for event in events.read() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 32.0,
+        };
+
+        controller.target_zoom =
+            (controller.target_zoom * 1.1_f32.powf(-lines)).clamp(ZOOM_MIN, ZOOM_MAX);
+    }
+}
+
+fn zoom_pinch(touches: Res<Touches>, mut controller: Single<&mut CameraController>) {
+    let mut pressed = touches.iter();
+    let (Some(first), Some(second), None) = (pressed.next(), pressed.next(), pressed.next()) else {
+        return;
+    };
+
+    let distance = first.position().distance(second.position());
+    let previous = first
+        .previous_position()
+        .distance(second.previous_position());
+
+    if distance < 1.0 || previous < 1.0 {
+        return;
+    }
+
+    // fingers moving apart zoom in
+    controller.target_zoom =
+        (controller.target_zoom * previous / distance).clamp(ZOOM_MIN, ZOOM_MAX);
+}
+
+fn apply_zoom(
+    time: Res<Time<Real>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut camera: Single<(&mut Projection, &mut CameraController)>,
+) {
+    let (projection, controller) = &mut *camera;
+
+    let Projection::Orthographic(projection) = &mut **projection else {
+        return;
+    };
+
+    let target_zoom = controller.target_zoom;
+    controller
+        .zoom
+        .smooth_nudge(&target_zoom, 10.0, time.delta_secs());
+
+    // cut off the long side of very wide or tall windows
+    let aspect_ratio = window.width() / window.height().max(1.0);
+    let elongation = aspect_ratio.max(aspect_ratio.recip());
+    let scale = controller.zoom * (MAX_ASPECT_RATIO / elongation).min(1.0);
+
+    if projection.scale != scale {
+        projection.scale = scale;
+    }
+}
@@ -0,0 +1,17 @@
+//! Wall clock access. `std::time::SystemTime` panics on the web, so we ask
+//! javascript for the current time there.
+
+/// Seconds since the unix epoch.
+#[cfg(target_arch = "wasm32")]
+pub fn unix_time_secs() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+/// Seconds since the unix epoch.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_time_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,202 @@
+use crate::game::enemy::{Awake, Enemy, EnemyKilled};
+use crate::game::hud::AddScore;
+use crate::game::movement::Movement;
+use crate::game::player::Player;
+use crate::game::screens::Screen;
+use crate::{AppSystems, PausableSystems};
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// Velocity multiplier while dashing.
+const DASH_BOOST: f32 = 4.0;
+
+const DASH_DURATION: Duration = Duration::from_millis(250);
+const DASH_COOLDOWN: Duration = Duration::from_secs(3);
+
+/// Maximum time between two clicks or taps to count as a double click.
+const DOUBLE_PRESS_WINDOW: Duration = Duration::from_millis(300);
+
+/// Sleeping enemies closer than this to a dashing player are killed before they block the way.
+const DASH_KILL_RADIUS: f32 = 40.0;
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<StartDash>();
+
+    app.add_systems(
+        Update,
+        (
+            dash_with_keyboard,
+            dash_with_gamepad,
+            dash_with_double_click,
+            dash_with_double_tap,
+        )
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::RecordInput)
+            .in_set(PausableSystems),
+    );
+
+    app.add_systems(
+        FixedUpdate,
+        (start_dash, update_dash, kill_while_dashing)
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// Asks the player to dash if the cooldown allows it.
+#[derive(Event)]
+pub struct StartDash;
+
+#[derive(Component)]
+pub struct Dash {
+    pub cooldown: Timer,
+    active: Timer,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        let mut cooldown = Timer::new(DASH_COOLDOWN, TimerMode::Once);
+        cooldown.set_elapsed(DASH_COOLDOWN);
+
+        let mut active = Timer::new(DASH_DURATION, TimerMode::Once);
+        active.set_elapsed(DASH_DURATION);
+
+        Self { cooldown, active }
+    }
+}
+
+impl Dash {
+    pub fn is_active(&self) -> bool {
+        !self.active.finished()
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.cooldown.finished() && !self.is_active()
+    }
+}
+
+fn dash_with_keyboard(keys: Res<ButtonInput<KeyCode>>, mut dash: EventWriter<StartDash>) {
+    if keys.any_just_pressed([KeyCode::Space, KeyCode::ShiftLeft]) {
+        dash.write(StartDash);
+    }
+}
+
+fn dash_with_gamepad(gamepads: Query<&Gamepad>, mut dash: EventWriter<StartDash>) {
+    if gamepads
+        .iter()
+        .any(|gamepad| gamepad.just_pressed(GamepadButton::South))
+    {
+        dash.write(StartDash);
+    }
+}
+
+fn dash_with_double_click(
+    time: Res<Time<Real>>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    mut last_click: Local<Option<Duration>>,
+    mut dash: EventWriter<StartDash>,
+) {
+    if !mouse_button.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let now = time.elapsed();
+    if last_click.is_some_and(|last| now - last <= DOUBLE_PRESS_WINDOW) {
+        dash.write(StartDash);
+
+        // a third click starts counting again
+        *last_click = None;
+        return;
+    }
+
+    *last_click = Some(now);
+}
+
+fn dash_with_double_tap(
+    time: Res<Time<Real>>,
+    touches: Res<Touches>,
+    mut last_tap: Local<Option<Duration>>,
+    mut dash: EventWriter<StartDash>,
+) {
+    if !touches.any_just_pressed() {
+        return;
+    }
+
+    let now = time.elapsed();
+    if last_tap.is_some_and(|last| now - last <= DOUBLE_PRESS_WINDOW) {
+        dash.write(StartDash);
+        *last_tap = None;
+        return;
+    }
+
+    *last_tap = Some(now);
+}
+
+fn start_dash(mut events: EventReader<StartDash>, mut player: Single<&mut Dash, With<Player>>) {
+    if events.read().count() == 0 || !player.is_ready() {
+        return;
+    }
+
+    player.active.reset();
+    player.cooldown.reset();
+}
+
+fn update_dash(
+    time: Res<Time<Fixed>>,
+    mut player: Single<(&mut Dash, &mut Movement), With<Player>>,
+) {
+    let (dash, movement) = &mut *player;
+
+    dash.active.tick(time.delta());
+
+    // the cooldown starts once the dash is over
+    if !dash.is_active() {
+        dash.cooldown.tick(time.delta());
+    }
+
+    let boost = match dash.is_active() {
+        true => DASH_BOOST,
+        false => 1.0,
+    };
+
+    if movement.boost != boost {
+        movement.boost = boost;
+    }
+}
+
+fn kill_while_dashing(
+    mut commands: Commands,
+    mut player: Single<(&mut Player, &Dash, &Transform)>,
+    enemies: Query<(Entity, &Transform), (With<Enemy>, Without<Awake>)>,
+    mut add_score: EventWriter<AddScore>,
+    mut killed: EventWriter<EnemyKilled>,
+) {
+    let (player, dash, player_transform) = &mut *player;
+
+    if !dash.is_active() {
+        return;
+    }
+
+    let player_pos = player_transform.translation.xy();
+
+    for (enemy, enemy_transform) in &enemies {
+        let enemy_pos = enemy_transform.translation.xy();
+        if enemy_pos.distance(player_pos) > DASH_KILL_RADIUS {
+            continue;
+        }
+
+        add_score.write(AddScore {
+            position: enemy_pos,
+            score: player.add_kill(false),
+        });
+
+        killed.write(EnemyKilled {
+            position: enemy_pos,
+            awake: false,
+        });
+
+        commands.entity(enemy).despawn();
+    }
+}
@@ -0,0 +1,62 @@
+use crate::game::screens::Screen;
+use crate::game::squishy::Squishy;
+use crate::{PausableSystems, game};
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use std::time::Duration;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        decoy_expire
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(PausableSystems),
+    );
+}
+
+/// A fake player that awake enemies hunt instead of the real one.
+#[derive(Component)]
+pub struct Decoy {
+    lifetime: Timer,
+}
+
+pub fn decoy_bundle(assets: &game::Assets, position: Vec2, lifetime: Duration) -> impl Bundle {
+    (
+        Name::new("Decoy"),
+        StateScoped(Screen::Gameplay),
+        Decoy {
+            lifetime: Timer::new(lifetime, TimerMode::Once),
+        },
+        Transform::from_translation(position.extend(0.5)),
+        Sprite {
+            image: assets.player.clone(),
+            custom_size: Some(Vec2::splat(32.0)),
+            color: Color::oklcha(0.645, 0.130, 2.47, 0.6),
+            anchor: Anchor::Center,
+            ..default()
+        },
+        Squishy {
+            frequency: 4.0,
+            scale_min: vec2(1.0, 0.8),
+            scale_max: vec2(1.0, 1.2),
+            offset: Duration::ZERO,
+        },
+    )
+}
+
+fn decoy_expire(
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+    mut decoys: Query<(Entity, &mut Decoy, &mut Sprite)>,
+) {
+    for (entity, mut decoy, mut sprite) in &mut decoys {
+        if decoy.lifetime.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // fade out during the last second
+        let alpha = decoy.lifetime.remaining_secs().min(1.0) * 0.6;
+        sprite.color.set_alpha(alpha);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::game::enemy::EnemyKilled;
+use crate::game::health::Health;
+use crate::game::mode::GameMode;
+use crate::game::player::Player;
+use crate::game::screens::Screen;
+use crate::game::tuning::Tuning;
+use crate::{AppSystems, PausableSystems};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::ops::Range;
+
+/// Bounds of the multiplier applied by the dynamic difficulty.
+const MIN_SCALE: f32 = 0.8;
+const MAX_SCALE: f32 = 1.25;
+
+/// How fast the multiplier follows the performance of the player, per second.
+const SCALE_RATE: f32 = 0.02;
+
+/// Time window in seconds over which the kill rate is averaged.
+const KILL_RATE_WINDOW: f32 = 30.0;
+
+/// Kills per minute at which the dynamic difficulty stays at the preset.
+const REFERENCE_KILL_RATE: f32 = 20.0;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<DifficultySelection>();
+
+    app.add_systems(OnEnter(Screen::Reset), apply_difficulty);
+
+    app.add_systems(
+        FixedUpdate,
+        adjust_dynamic_difficulty
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum DifficultyPreset {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl DifficultyPreset {
+    pub const ALL: [DifficultyPreset; 3] = [
+        DifficultyPreset::Easy,
+        DifficultyPreset::Normal,
+        DifficultyPreset::Hard,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DifficultyPreset::Easy => "easy",
+            DifficultyPreset::Normal => "normal",
+            DifficultyPreset::Hard => "hard",
+        }
+    }
+
+    /// Stable identifier of the preset, the dynamic difficulty counts as a separate one.
+    pub fn id(self, dynamic: bool) -> String {
+        match dynamic {
+            true => format!("{}-dynamic", self.label()),
+            false => self.label().to_string(),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let idx = Self::ALL
+            .iter()
+            .position(|p| *p == self)
+            .unwrap_or_default();
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+}
+
+/// The difficulty the player selected in the menu.
+#[derive(Resource, Copy, Clone, Debug, Default)]
+pub struct DifficultySelection {
+    pub preset: DifficultyPreset,
+    pub dynamic: bool,
+}
+
+/// Enemy tuning of a single preset, loaded from the tuning file.
+#[derive(Clone, Debug, Deserialize)]
+pub struct DifficultyTuning {
+    /// Number of enemies placed on the map.
+    pub enemies: usize,
+    /// Maximum number of enemies awake at the same time, the furthest fall asleep again.
+    pub max_awake: usize,
+    /// Distance at which a sleeping enemy notices the player.
+    pub wake_distance_player: f32,
+    /// Distance at which a sleeping enemy is woken up by an awake one.
+    pub wake_distance_enemy: f32,
+    pub wake_delay_player: Range<f32>,
+    pub wake_delay_enemy: Range<f32>,
+    pub enemy_speed: Range<f32>,
+    /// Time between two course corrections of an awake enemy.
+    pub reorient_secs: Range<f32>,
+}
+
+/// Difficulty of the current run, derived from the selection when a run starts.
+#[derive(Resource, Clone, Debug)]
+pub struct Difficulty {
+    pub preset: DifficultyPreset,
+    pub tuning: DifficultyTuning,
+
+    /// Adjusts `scale` during the run based on how the player is doing.
+    pub dynamic: bool,
+    /// Multiplier for enemy speed and wake distances, stays at one unless dynamic.
+    pub scale: f32,
+    /// Kills per minute, averaged over the last seconds.
+    kill_rate: f32,
+}
+
+impl Difficulty {
+    pub fn new(preset: DifficultyPreset, tuning: &Tuning, dynamic: bool) -> Self {
+        Self {
+            preset,
+            tuning: tuning.difficulty(preset).clone(),
+            dynamic,
+            scale: 1.0,
+            kill_rate: REFERENCE_KILL_RATE,
+        }
+    }
+
+    /// Speed range of awake enemies with the dynamic multiplier applied.
+    pub fn speed(&self) -> Range<f32> {
+        let speed = &self.tuning.enemy_speed;
+        speed.start * self.scale..speed.end * self.scale
+    }
+
+    pub fn wake_distance(&self, by_player: bool) -> f32 {
+        let distance = match by_player {
+            true => self.tuning.wake_distance_player,
+            false => self.tuning.wake_distance_enemy,
+        };
+
+        distance * self.scale
+    }
+}
+
+fn apply_difficulty(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    selection: Res<DifficultySelection>,
+    tuning: Res<Tuning>,
+) {
+    let difficulty = match *mode {
+        // everybody plays the daily challenge under the same conditions
+        GameMode::Daily => Difficulty::new(DifficultyPreset::Normal, &tuning, false),
+        _ => Difficulty::new(selection.preset, &tuning, selection.dynamic),
+    };
+
+    commands.insert_resource(difficulty);
+}
+
+/// Makes the enemies faster and more alert while the player kills a lot without
+/// taking damage, and eases off once the player is struggling.
+fn adjust_dynamic_difficulty(
+    time: Res<Time<Fixed>>,
+    mut difficulty: ResMut<Difficulty>,
+    mut killed: EventReader<EnemyKilled>,
+    player: Single<&Health, With<Player>>,
+) {
+    let kills = killed.read().count();
+
+    if !difficulty.dynamic {
+        return;
+    }
+
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+
+    // exponential moving average, converges to the number of kills per minute
+    let decay = (-dt / KILL_RATE_WINDOW).exp();
+    difficulty.kill_rate = difficulty.kill_rate * decay + kills as f32 * 60.0 / KILL_RATE_WINDOW;
+
+    // 1.0 is a player killing at the reference rate with full health
+    let skill = (difficulty.kill_rate / REFERENCE_KILL_RATE).min(2.0);
+    let health = player.current as f32 / player.max as f32;
+    let performance = skill * health;
+
+    let target = match performance >= 1.0 {
+        true => 1.0 + (performance - 1.0) * (MAX_SCALE - 1.0),
+        false => MIN_SCALE + performance * (1.0 - MIN_SCALE),
+    };
+
+    let step = SCALE_RATE * dt;
+    difficulty.scale += (target - difficulty.scale).clamp(-step, step);
+}
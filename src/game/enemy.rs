@@ -1,11 +1,16 @@
+use crate::game::decoy::Decoy;
+use crate::game::difficulty::Difficulty;
+use crate::game::perception::{Noise, NoiseKind, line_of_sight};
 use crate::game::player::Player;
 use crate::game::rand::Rand;
 use crate::game::screens::Screen;
 use crate::game::squishy::Squishy;
+use crate::game::state_machine::{AddMachine, Machine, MachineState, Transition, initial_state};
+use crate::game::tuning::Tuning;
 use crate::{AppSystems, game};
 use avian2d::prelude::{
-    AngularVelocity, Collider, ColliderDisabled, ExternalForce, LinearDamping, LinearVelocity,
-    MaxLinearSpeed, RigidBody,
+    AngularVelocity, Collider, ColliderDisabled, ComputedMass, ExternalForce, LinearDamping,
+    LinearVelocity, MaxLinearSpeed, RigidBody, SpatialQuery,
 };
 use bevy::math::FloatPow;
 use bevy::prelude::*;
@@ -17,26 +22,63 @@ use std::ops::Range;
 use std::time::Duration;
 
 pub fn plugin(app: &mut App) {
+    app.add_event::<EnemyKilled>();
+    app.add_machine::<Behaviour>();
+
     app.add_systems(
-        Update,
+        FixedUpdate,
         (
             enable_disable_colliders_for_not_awake,
             state_sleeping,
             state_awaking,
             state_awake_hunt_player,
-            state_awake_avoid_collisions,
+            state_awake_flocking,
             restrict_number_of_enemies_awake,
-            enemy_sync_image,
         )
             .chain()
             .run_if(in_state(Screen::Gameplay))
             .in_set(AppSystems::Update),
     );
+
+    app.add_systems(
+        Update,
+        enemy_sync_image
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update),
+    );
 }
 
 #[derive(Component)]
 pub struct Enemy;
 
+/// Decides how an awake enemy moves together with others of its kind.
+#[derive(Component, Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Archetype {
+    /// Hunts the player on its own.
+    #[default]
+    Straggler,
+    /// Moves in tight groups heading the same way.
+    Pack,
+    /// Clusters loosely around its neighbours.
+    Swarm,
+}
+
+impl Archetype {
+    pub const ALL: [Archetype; 3] = [Archetype::Straggler, Archetype::Pack, Archetype::Swarm];
+}
+
+/// Written whenever an enemy was killed by the player.
+#[derive(Event)]
+pub struct EnemyKilled {
+    pub position: Vec2,
+    pub awake: bool,
+}
+
+/// The lifecycle of an enemy, its states are [`Sleeping`], [`Awaking`] and [`Awake`].
+pub struct Behaviour;
+
+impl Machine for Behaviour {}
+
 #[derive(Component)]
 pub struct Sleeping {
     pub when: Duration,
@@ -46,6 +88,8 @@ pub struct Sleeping {
 pub struct Awaking {
     // awake once the timer hits zero
     pub timer: Timer,
+    /// Where the enemy believes the player to be once it is awake.
+    pub alerted_to: Vec2,
 }
 
 #[derive(Component)]
@@ -53,24 +97,100 @@ pub struct Awake {
     pub since: Duration,
     pub seed: f32,
     pub reorient: Timer,
+    /// Position the player was last seen or heard at.
+    pub last_seen: Vec2,
+    /// Set while the enemy can neither see nor hear the player.
+    pub lost_since: Option<Duration>,
+}
+
+/// Delay until an enemy woken up by an explosion starts moving.
+const WAKE_DELAY_EXPLOSION: Range<f32> = 0.2..0.8;
+
+/// An enemy that lost track of the player searches this far around the last known position.
+const SEARCH_RADIUS: f32 = 128.0;
+
+/// An enemy that lost track of the player gives up and falls asleep after this time.
+const SEARCH_DURATION: Duration = Duration::from_secs(8);
+
+impl MachineState for Sleeping {
+    type Machine = Behaviour;
+
+    fn enter(entity: &mut EntityWorldMut) {
+        entity.insert(ColliderDisabled);
+    }
+}
+
+impl MachineState for Awaking {
+    type Machine = Behaviour;
+
+    fn enter(entity: &mut EntityWorldMut) {
+        let now = entity.resource::<Time<Virtual>>().elapsed();
+
+        entity.remove::<ColliderDisabled>().insert(Squishy {
+            frequency: 1.0,
+            scale_max: Vec2::splat(1.1),
+            scale_min: Vec2::splat(1.0),
+            offset: now,
+        });
+    }
+
+    fn exit(entity: &mut EntityWorldMut) {
+        entity.remove::<Squishy>();
+    }
+}
+
+impl MachineState for Awake {
+    type Machine = Behaviour;
+
+    fn enter(entity: &mut EntityWorldMut) {
+        let frequency = entity.resource_mut::<Rand>().random_range(1.8..2.2);
+
+        if let Some(mut transform) = entity.get_mut::<Transform>() {
+            transform.scale = Vec3::ONE;
+        }
+
+        entity
+            .remove::<(LinearDamping, ColliderDisabled)>()
+            .insert(Squishy {
+                frequency,
+                scale_min: vec2(0.9, 1.0),
+                scale_max: vec2(1.09, 1.0),
+                offset: Duration::ZERO,
+            });
+    }
+
+    fn exit(entity: &mut EntityWorldMut) {
+        entity.remove::<Squishy>();
+    }
+}
+
+/// Puts an awake or awaking enemy back to sleep.
+pub fn fall_asleep(commands: &mut Commands, enemy: Entity, now: Duration) {
+    commands.entity(enemy).transition(Sleeping { when: now });
 }
 
 impl Awaking {
-    pub fn new(rand: &mut impl Rng, delay_secs_range: Range<f32>) -> Self {
+    pub fn new(rand: &mut impl Rng, delay_secs_range: Range<f32>, alerted_to: Vec2) -> Self {
         let delay_secs = rand.random_range(delay_secs_range);
 
         Self {
             timer: Timer::new(Duration::from_secs_f32(delay_secs), TimerMode::Once),
+            alerted_to,
         }
     }
 }
 
-pub fn enemy_bundle(rand: &mut Rand, assets: &game::Assets) -> impl Bundle {
+/// A sleeping enemy, `asleep_since` keeps it from waking up again right away.
+pub fn enemy_bundle(
+    assets: &game::Assets,
+    archetype: Archetype,
+    max_speed: f32,
+    asleep_since: Duration,
+) -> impl Bundle {
     (
         Enemy,
-        Sleeping {
-            when: Duration::ZERO,
-        },
+        archetype,
+        initial_state(Sleeping { when: asleep_since }),
         Sprite {
             image: assets.enemy.clone(),
             custom_size: Some(Vec2::splat(48.0)),
@@ -81,12 +201,25 @@ pub fn enemy_bundle(rand: &mut Rand, assets: &game::Assets) -> impl Bundle {
         RigidBody::Dynamic,
         Collider::rectangle(20.0, 20.0),
         LinearVelocity::ZERO,
-        MaxLinearSpeed(rand.random_range(100.0..140.0)),
+        MaxLinearSpeed(max_speed),
         ExternalForce::ZERO.with_persistence(false),
         ColliderDisabled,
     )
 }
 
+impl Awake {
+    /// An enemy that starts hunting the player at the given position.
+    pub fn new(rand: &mut impl Rng, now: Duration, last_seen: Vec2) -> Self {
+        Self {
+            since: now,
+            seed: rand.random_range(0.0..200.0),
+            reorient: Timer::default(),
+            last_seen,
+            lost_since: None,
+        }
+    }
+}
+
 fn enable_disable_colliders_for_not_awake(
     mut commands: Commands,
     player: Single<&Transform, With<Player>>,
@@ -146,28 +279,16 @@ fn state_sleeping(
     mut rand: ResMut<Rand>,
     mut commands: Commands,
     mut enemies: Query<(Entity, &Transform, &Sleeping), With<Sleeping>>,
-    time: Res<Time<Virtual>>,
-    query_players: Query<&Transform, With<Player>>,
-    query_runners: Query<&Transform, (With<Enemy>, With<Awake>)>,
+    time: Res<Time<Fixed>>,
+    difficulty: Res<Difficulty>,
+    mut noise: EventReader<Noise>,
+    query_runners: Query<(&Transform, &Awake), With<Enemy>>,
 ) {
-    enum Other<'a> {
-        Player(&'a Transform),
-        Enemy(&'a Transform),
-    }
-
-    impl Other<'_> {
-        fn position(&self) -> Vec2 {
-            match self {
-                Other::Player(tr) => tr.translation.xy(),
-                Other::Enemy(tr) => tr.translation.xy(),
-            }
-        }
-    }
+    let noises: Vec<_> = noise.read().copied().collect();
 
-    let others: Vec<_> = query_players
+    let runners: Vec<_> = query_runners
         .iter()
-        .map(Other::Player)
-        .chain(query_runners.iter().map(Other::Enemy))
+        .map(|(transform, awake)| (transform.translation.xy(), awake.last_seen))
         .collect();
 
     for (enemy_id, enemy_transform, enemy_sleeping) in &mut enemies {
@@ -176,108 +297,155 @@ fn state_sleeping(
             continue;
         }
 
-        // get the nearest entity to this one
-        let Some(other) = others.iter().min_by_key(|other| {
-            OrderedFloat(other.position().distance(enemy_transform.translation.xy()))
-        }) else {
-            continue;
-        };
+        let enemy_pos = enemy_transform.translation.xy();
 
-        // get distance to the player
-        let offset = other.position() - enemy_transform.translation.xy();
+        // the player and explosions are heard, awake enemies wake up their neighbours
+        let heard = noises.iter().find(|noise| noise.is_heard_at(enemy_pos));
 
-        let (max_distance, delay_secs_range) = match other {
-            Other::Player(_player) => (64.0, 2.0..3.0),
-            Other::Enemy(_runner) => (128.0, 0.5..1.0),
-        };
+        let (delay_secs_range, alerted_to) = match heard {
+            Some(noise) => match noise.kind {
+                NoiseKind::Player => (difficulty.tuning.wake_delay_player.clone(), noise.position),
+                NoiseKind::Explosion => (WAKE_DELAY_EXPLOSION, noise.position),
+            },
 
-        if offset.length() > max_distance {
-            // too far away, skipping this one
-            continue;
-        }
+            None => {
+                // get the nearest awake enemy to this one
+                let Some((runner_pos, runner_last_seen)) = runners
+                    .iter()
+                    .min_by_key(|(position, _)| OrderedFloat(position.distance(enemy_pos)))
+                else {
+                    continue;
+                };
+
+                if runner_pos.distance(enemy_pos) > difficulty.wake_distance(false) {
+                    // too far away, skipping this one
+                    continue;
+                }
+
+                (
+                    difficulty.tuning.wake_delay_enemy.clone(),
+                    *runner_last_seen,
+                )
+            }
+        };
 
         // wake the guy up and go into the direction of the player
-        commands
-            .entity(enemy_id)
-            .remove::<(Sleeping, ColliderDisabled)>()
-            .try_insert((
-                Awaking::new(rand.as_mut(), delay_secs_range),
-                Squishy {
-                    frequency: 1.0,
-                    scale_max: Vec2::splat(1.1),
-                    scale_min: Vec2::splat(1.0),
-                    offset: time.elapsed(),
-                },
-            ));
+        commands.entity(enemy_id).transition(Awaking::new(
+            rand.as_mut(),
+            delay_secs_range,
+            alerted_to,
+        ));
     }
 }
 
 fn state_awaking(
-    time: Res<Time<Virtual>>,
+    time: Res<Time<Fixed>>,
     mut commands: Commands,
     mut rand: ResMut<Rand>,
-    mut enemies: Query<(Entity, &mut Transform, &mut Awaking)>,
+    mut enemies: Query<(Entity, &mut Awaking)>,
 ) {
-    for (entity, mut transform, mut awaking) in &mut enemies {
+    for (entity, mut awaking) in &mut enemies {
         if !awaking.timer.tick(time.delta()).just_finished() {
             continue;
         }
 
-        transform.scale = Vec3::splat(1.0);
-
-        commands
-            .entity(entity)
-            .remove::<(Awaking, Squishy, LinearDamping, ColliderDisabled)>()
-            .insert((
-                Awake {
-                    since: time.elapsed(),
-                    seed: rand.random_range(0.0..200.0),
-                    reorient: Timer::default(),
-                },
-                Squishy {
-                    frequency: rand.random_range(1.8..2.2),
-                    scale_min: vec2(0.9, 1.0),
-                    scale_max: vec2(1.09, 1.0),
-                    offset: Duration::ZERO,
-                },
-            ));
+        commands.entity(entity).transition(Awake::new(
+            rand.as_mut(),
+            time.elapsed(),
+            awaking.alerted_to,
+        ));
     }
 }
 
+/// Hunts the nearest target in sight, or searches the position it was last seen or heard at.
 fn state_awake_hunt_player(
+    mut commands: Commands,
     mut rand: ResMut<Rand>,
-    time: Res<Time<Virtual>>,
-    mut enemies: Query<(&Transform, &mut Awake, &mut LinearVelocity), With<Enemy>>,
+    time: Res<Time<Fixed>>,
+    difficulty: Res<Difficulty>,
+    spatial_query: SpatialQuery,
+    bodies: Query<&RigidBody>,
+    mut noise: EventReader<Noise>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Awake,
+            &mut LinearVelocity,
+            &mut MaxLinearSpeed,
+        ),
+        With<Enemy>,
+    >,
     players: Query<&Transform, With<Player>>,
+    decoys: Query<&Transform, With<Decoy>>,
 ) {
-    let players: Vec<_> = players.iter().collect();
+    // a decoy distracts the enemies from any player
+    let targets: Vec<_> = match decoys.is_empty() {
+        true => players.iter().map(|tr| tr.translation.xy()).collect(),
+        false => decoys.iter().map(|tr| tr.translation.xy()).collect(),
+    };
+
+    // footsteps of the player can not be heard over a decoy
+    let footsteps: Vec<_> = noise
+        .read()
+        .filter(|noise| noise.kind == NoiseKind::Player && decoys.is_empty())
+        .copied()
+        .collect();
 
-    for (enemy_transform, mut enemy_awake, mut enemy_movement) in &mut enemies {
+    for (enemy, enemy_transform, mut enemy_awake, mut enemy_movement, mut max_speed) in &mut enemies
+    {
         if !enemy_awake.reorient.tick(time.delta()).just_finished() {
             continue;
         }
 
         // re-init the timer to reorient later
         enemy_awake.reorient = Timer::new(
-            Duration::from_secs_f32(rand.random_range(1.0..2.0)),
+            Duration::from_secs_f32(rand.random_range(difficulty.tuning.reorient_secs.clone())),
             TimerMode::Once,
         );
 
-        // get the player that is nearest
-        let Some(player) = players.iter().min_by_key(|p| {
-            OrderedFloat(
-                p.translation
-                    .xy()
-                    .distance(enemy_transform.translation.xy()),
-            )
-        }) else {
-            continue;
+        let enemy_pos = enemy_transform.translation.xy();
+
+        // get the target that is nearest and not hidden behind an obstacle
+        let seen = targets
+            .iter()
+            .copied()
+            .filter(|target| line_of_sight(&spatial_query, &bodies, enemy_pos, *target))
+            .min_by_key(|target| OrderedFloat(target.distance(enemy_pos)));
+
+        let heard = footsteps
+            .iter()
+            .find(|noise| noise.is_heard_at(enemy_pos))
+            .map(|noise| noise.position);
+
+        let target = match seen.or(heard) {
+            Some(position) => {
+                enemy_awake.last_seen = position;
+                enemy_awake.lost_since = None;
+                position + rand.vec2() * 32.0
+            }
+
+            None => {
+                let lost_since = *enemy_awake.lost_since.get_or_insert(time.elapsed());
+                if time.elapsed() - lost_since > SEARCH_DURATION {
+                    // give up searching
+                    enemy_movement.0 = Vec2::ZERO;
+                    fall_asleep(&mut commands, enemy, time.elapsed());
+                    continue;
+                }
+
+                // search around the last known position
+                enemy_awake.last_seen + rand.vec2() * SEARCH_RADIUS
+            }
         };
 
         // get vector to target
-        let target = player.translation.xy() + rand.vec2() * 32.0;
-        let offset = target - enemy_transform.translation.xy();
-        enemy_movement.0 = offset.normalize() * rand.random_range(100.0..140.0);
+        let offset = target - enemy_pos;
+        let speed = rand.random_range(difficulty.speed());
+        enemy_movement.0 = offset.normalize_or_zero() * speed;
+
+        // follow changes of the dynamic difficulty
+        max_speed.0 = speed;
     }
 }
 
@@ -294,14 +462,15 @@ fn restrict_number_of_enemies_awake(
         (With<Enemy>, With<Awake>),
     >,
     player: Single<&Transform, With<Player>>,
-    time: Res<Time<Virtual>>,
+    time: Res<Time<Fixed>>,
+    difficulty: Res<Difficulty>,
 ) {
-    const MAX: usize = 256;
+    let max = difficulty.tuning.max_awake;
 
     // disable enemies that are furthest away from the player, but only if we have more
     // than N active enemies
     let mut enemies: Vec<_> = enemies.iter_mut().collect::<Vec<_>>();
-    if enemies.len() < MAX {
+    if enemies.len() < max {
         return;
     }
 
@@ -310,46 +479,92 @@ fn restrict_number_of_enemies_awake(
         OrderedFloat(tr.translation.distance(player.translation))
     });
 
-    for (id, _, mov, angvel, force) in enemies.iter_mut().skip(MAX) {
+    for (id, _, mov, angvel, force) in enemies.iter_mut().skip(max) {
         mov.0 = Vec2::ZERO;
         angvel.0 = 0.0;
         force.set_force(Vec2::ZERO);
 
         // revert into sleeping state
-        commands.entity(*id).remove::<(Awake, Squishy)>().insert((
-            Sleeping {
-                when: time.elapsed(),
-            },
-            ColliderDisabled,
-        ));
+        fall_asleep(&mut commands, *id, time.elapsed());
     }
 }
 
-fn state_awake_avoid_collisions(mut enemies: Query<(&mut ExternalForce, &Transform), With<Awake>>) {
-    let mut enemies: Vec<_> = enemies.iter_mut().collect();
+/// Steering gain towards the average velocity of the neighbours, per second.
+const ALIGNMENT_GAIN: f32 = 2.0;
 
-    for idx in 0..enemies.len() {
-        let (_, transform) = enemies[idx];
-        let position = transform.translation.xy();
+/// Steering gain towards the center of the neighbours, per second squared.
+const COHESION_GAIN: f32 = 0.5;
 
-        let mut new_force = Vec2::ZERO;
+/// Keeps awake enemies apart and, depending on their archetype, lets them move as packs.
+fn state_awake_flocking(
+    tuning: Res<Tuning>,
+    mut enemies: Query<
+        (
+            &mut ExternalForce,
+            &Transform,
+            &LinearVelocity,
+            &ComputedMass,
+            &Archetype,
+        ),
+        With<Awake>,
+    >,
+) {
+    let flocking = &tuning.flocking;
+
+    // snapshot of all awake enemies, the forces are applied afterwards
+    let boids: Vec<_> = enemies
+        .iter()
+        .map(|(_, transform, velocity, _, archetype)| {
+            (transform.translation.xy(), velocity.0, *archetype)
+        })
+        .collect();
+
+    for (idx, (mut force, _, velocity, mass, archetype)) in enemies.iter_mut().enumerate() {
+        let (position, _, _) = boids[idx];
+        let weights = flocking.archetype(*archetype);
+
+        let mut separation = Vec2::ZERO;
+        let mut velocity_sum = Vec2::ZERO;
+        let mut position_sum = Vec2::ZERO;
+        let mut flockmates = 0;
 
-        // calculate force
-        for (other_idx, (_, other)) in enemies.iter().enumerate() {
+        for (other_idx, (other_position, other_velocity, other_archetype)) in
+            boids.iter().enumerate()
+        {
             if other_idx == idx {
                 continue;
             }
 
-            let other_position = other.translation.xy();
+            let distance = position.distance(*other_position);
+            if distance < flocking.separation_radius {
+                let direction = (position - *other_position).normalize_or_zero();
+                separation += direction * (1000000.0 / distance).min(1000000.0);
+            }
 
-            let distance = position.distance(other_position);
-            if position.distance(other_position) < 64.0 {
-                let direction = (position - other_position).normalize();
-                new_force += direction * (1000000.0 / distance).min(1000000.0);
+            // only enemies of the same archetype form a flock
+            if flocking.enabled
+                && distance < flocking.neighbor_radius
+                && *other_archetype == *archetype
+            {
+                velocity_sum += *other_velocity;
+                position_sum += *other_position;
+                flockmates += 1;
             }
         }
 
-        let (force, _) = &mut enemies[idx];
+        let mut new_force = separation * weights.separation;
+
+        if flockmates > 0 {
+            let average_velocity = velocity_sum / flockmates as f32;
+            let center = position_sum / flockmates as f32;
+
+            let alignment = (average_velocity - velocity.0) * ALIGNMENT_GAIN;
+            let cohesion = (center - position) * COHESION_GAIN;
+
+            new_force +=
+                (alignment * weights.alignment + cohesion * weights.cohesion) * mass.value();
+        }
+
         force.apply_force(new_force);
     }
 }
@@ -0,0 +1,384 @@
+use crate::game;
+use crate::game::audio::{PlaySound, Sound};
+use crate::game::camera::ShakeCamera;
+use crate::game::enemy::{Awake, Enemy, EnemyKilled};
+use crate::game::hud::AddScore;
+use crate::game::particles::{EmitParticles, ParticleEffect};
+use crate::game::perception::Noise;
+use crate::game::player::Player;
+use crate::game::screens::Screen;
+use avian2d::prelude::TranslationInterpolation;
+use bevy::math::FloatPow;
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Radius of a blast that shakes the camera with the maximum trauma.
+const FULL_SHAKE_RADIUS: f32 = 250.0;
+
+/// Distance at which an awake enemy sets off a mine.
+const MINE_TRIGGER_RADIUS: f32 = 32.0;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (
+            follow_owner,
+            tick_delayed_explosions,
+            trigger_mines,
+            detonate_blasts,
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay).and(resource_exists::<game::Assets>)),
+    );
+
+    app.add_systems(
+        Update,
+        explosion_fade_out.run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Makes awake enemies killed by an explosion blow up themselves.
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct ChainSettings {
+    /// Radius of the secondary blasts.
+    pub radius: f32,
+    /// Delay between a kill and the blast it triggers.
+    pub delay_secs: f32,
+    /// Maximum number of links in the chain after the initial blast.
+    pub max_links: u32,
+}
+
+/// Where a delayed explosion goes off.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Placement {
+    /// Moves along with its owner.
+    #[default]
+    Owner,
+    /// Stays where it was dropped and also goes off when an awake enemy gets close.
+    Mine,
+}
+
+/// An explosion waiting for its timer. The position is taken from its transform.
+#[derive(Component)]
+pub struct DelayedExplosion {
+    pub timer: Timer,
+    pub radius: f32,
+    pub chain: Option<ChainSettings>,
+    pub placement: Placement,
+    /// The entity that caused the explosion.
+    pub owner: Entity,
+}
+
+#[derive(Component)]
+struct DelayedExplosionLabel;
+
+pub fn delayed_explosion_bundle(
+    assets: &game::Assets,
+    explosion: DelayedExplosion,
+    position: Vec2,
+) -> impl Bundle {
+    let mine = explosion.placement == Placement::Mine;
+
+    (
+        Name::new("Delayed explosion"),
+        StateScoped(Screen::Gameplay),
+        Transform::from_translation(position.extend(0.5)),
+        // follows its owner in the fixed timestep
+        TranslationInterpolation,
+        Sprite {
+            image: assets.circle.clone(),
+            anchor: Anchor::Center,
+            custom_size: Some(Vec2::splat(20.0)),
+            color: Color::oklcha(0.668, 0.224, 36.99, 0.9),
+            ..default()
+        },
+        match mine {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        },
+        explosion,
+        children![(
+            DelayedExplosionLabel,
+            Text2d::default(),
+            Anchor::BottomLeft,
+            Transform::from_xyz(24.0, 24.0, 1.0),
+            // the parent sprite is hidden when following the owner
+            Visibility::Visible,
+        )],
+    )
+}
+
+/// Tracks all blasts caused by a single explosion, so the score can be shown as a total.
+#[derive(Component)]
+struct Chain {
+    settings: Option<ChainSettings>,
+    origin: Vec2,
+    score: u32,
+    pending: u32,
+}
+
+/// A blast that goes off once the timer finished.
+#[derive(Component)]
+struct Blast {
+    timer: Timer,
+    radius: f32,
+    chain: Entity,
+    /// Number of blasts before this one in the chain.
+    link: u32,
+}
+
+fn blast_bundle(
+    position: Vec2,
+    radius: f32,
+    delay: Duration,
+    chain: Entity,
+    link: u32,
+) -> impl Bundle {
+    (
+        Name::new("Blast"),
+        StateScoped(Screen::Gameplay),
+        Transform::from_translation(position.extend(0.0)),
+        Blast {
+            timer: Timer::new(delay, TimerMode::Once),
+            radius,
+            chain,
+            link,
+        },
+    )
+}
+
+/// Starts a new explosion at the given position.
+pub struct Detonate {
+    pub position: Vec2,
+    pub radius: f32,
+    pub chain: Option<ChainSettings>,
+}
+
+impl Command for Detonate {
+    fn apply(self, world: &mut World) {
+        let chain = world
+            .spawn((
+                Name::new("Chain"),
+                StateScoped(Screen::Gameplay),
+                Chain {
+                    settings: self.chain,
+                    origin: self.position,
+                    score: 0,
+                    pending: 1,
+                },
+            ))
+            .id();
+
+        world.spawn(blast_bundle(
+            self.position,
+            self.radius,
+            Duration::ZERO,
+            chain,
+            0,
+        ));
+    }
+}
+
+fn follow_owner(
+    mut explosions: Query<(&DelayedExplosion, &mut Transform)>,
+    owners: Query<&Transform, Without<DelayedExplosion>>,
+) {
+    for (explosion, mut transform) in &mut explosions {
+        if explosion.placement != Placement::Owner {
+            continue;
+        }
+
+        if let Ok(owner) = owners.get(explosion.owner) {
+            transform.translation = owner.translation.xy().extend(transform.translation.z);
+        }
+    }
+}
+
+fn tick_delayed_explosions(
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+    mut explosions: Query<(Entity, &mut DelayedExplosion, &Transform, &Children)>,
+    mut labels: Query<&mut Text2d, With<DelayedExplosionLabel>>,
+) {
+    for (entity, mut explosion, transform, children) in &mut explosions {
+        if !explosion.timer.tick(time.delta()).just_finished() {
+            let mut labels = labels.iter_many_mut(children);
+            while let Some(mut text) = labels.fetch_next() {
+                text.0 = format!("boom in {:1.2}s", explosion.timer.remaining_secs());
+            }
+
+            continue;
+        }
+
+        // removes the label too
+        commands.entity(entity).despawn();
+
+        commands.queue(Detonate {
+            position: transform.translation.xy(),
+            radius: explosion.radius,
+            chain: explosion.chain,
+        });
+    }
+}
+
+fn trigger_mines(
+    mut explosions: Query<(&mut DelayedExplosion, &Transform)>,
+    enemies: Query<&Transform, (With<Enemy>, With<Awake>)>,
+) {
+    for (mut explosion, transform) in &mut explosions {
+        if explosion.placement != Placement::Mine || explosion.timer.finished() {
+            continue;
+        }
+
+        let position = transform.translation.xy();
+
+        let triggered = enemies
+            .iter()
+            .any(|enemy| enemy.translation.xy().distance(position) < MINE_TRIGGER_RADIUS);
+
+        if triggered {
+            // goes off during the next tick
+            let duration = explosion.timer.duration();
+            explosion.timer.set_elapsed(duration);
+        }
+    }
+}
+
+fn detonate_blasts(
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+    assets: Res<game::Assets>,
+    mut blasts: Query<(Entity, &mut Blast, &Transform)>,
+    mut chains: Query<(Entity, &mut Chain)>,
+    enemies: Query<(Entity, &Transform, Has<Awake>), (With<Enemy>, Without<Blast>)>,
+    mut player: Single<&mut Player>,
+    mut add_score: EventWriter<AddScore>,
+    mut killed: EventWriter<EnemyKilled>,
+    mut shake: EventWriter<ShakeCamera>,
+    mut particles: EventWriter<EmitParticles>,
+    mut sounds: EventWriter<PlaySound>,
+    mut noise: EventWriter<Noise>,
+    // enemies are despawned at the end of the system, make sure to not kill them twice
+    mut killed_enemies: Local<HashSet<Entity>>,
+) {
+    killed_enemies.clear();
+
+    for (blast_entity, mut blast, blast_transform) in &mut blasts {
+        if !blast.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        commands.entity(blast_entity).despawn();
+
+        let Ok((chain_entity, mut chain)) = chains.get_mut(blast.chain) else {
+            continue;
+        };
+
+        chain.pending -= 1;
+
+        let position = blast_transform.translation.xy();
+        let blast_radius = blast.radius;
+
+        shake.write(ShakeCamera {
+            trauma: 0.6 * (blast_radius / FULL_SHAKE_RADIUS).min(1.0),
+        });
+
+        sounds.write(PlaySound {
+            sound: Sound::Explosion,
+            position,
+        });
+
+        particles.write(EmitParticles {
+            position,
+            effect: ParticleEffect::Shockwave {
+                radius: blast_radius,
+            },
+        });
+
+        // the bang wakes up sleeping enemies further away
+        noise.write(Noise::explosion(position, blast_radius));
+
+        for (enemy, enemy_transform, enemy_is_awake) in enemies {
+            let enemy_pos = enemy_transform.translation.xy();
+
+            if enemy_pos.distance(position) > blast_radius || !killed_enemies.insert(enemy) {
+                continue;
+            }
+
+            // kill enemy
+            commands.entity(enemy).despawn();
+
+            chain.score += player.add_chain_kill(enemy_is_awake, blast.link);
+
+            killed.write(EnemyKilled {
+                position: enemy_pos,
+                awake: enemy_is_awake,
+            });
+
+            // awake enemies continue the chain
+            if enemy_is_awake
+                && let Some(settings) = chain.settings
+                && blast.link < settings.max_links
+            {
+                chain.pending += 1;
+
+                commands.spawn(blast_bundle(
+                    enemy_pos,
+                    settings.radius,
+                    Duration::from_secs_f32(settings.delay_secs),
+                    blast.chain,
+                    blast.link + 1,
+                ));
+            }
+        }
+
+        // spawn an explosion circle
+        commands.spawn((
+            Name::new("Explosion"),
+            StateScoped(Screen::Gameplay),
+            Transform::from_translation(position.extend(0.0)),
+            Explosion(Timer::from_seconds(0.25, TimerMode::Once)),
+            Sprite {
+                image: assets.circle.clone(),
+                anchor: Anchor::Center,
+                custom_size: Some(Vec2::splat(2.0 * blast_radius * 1.1)),
+                color: Color::srgba(1.0, 1.0, 1.0, 0.75),
+                ..default()
+            },
+        ));
+
+        if chain.pending == 0 {
+            if chain.score > 0 {
+                add_score.write(AddScore {
+                    score: chain.score,
+                    position: chain.origin,
+                });
+            }
+
+            commands.entity(chain_entity).despawn();
+        }
+    }
+}
+
+#[derive(Component)]
+struct Explosion(Timer);
+
+fn explosion_fade_out(
+    mut commands: Commands,
+    mut explosions: Query<(Entity, &mut Sprite, &mut Explosion)>,
+    time: ResMut<Time<Virtual>>,
+) {
+    for (entity, mut sprite, mut explosion) in &mut explosions {
+        if explosion.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let alpha = explosion.0.fraction_remaining().squared();
+        sprite.color.set_alpha(alpha);
+    }
+}
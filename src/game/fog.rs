@@ -0,0 +1,213 @@
+use crate::AppSystems;
+use crate::game;
+use crate::game::cursor::MainCamera;
+use crate::game::enemy::{Enemy, Sleeping};
+use crate::game::player::Player;
+use crate::game::screens::Screen;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use std::time::Duration;
+
+/// Size of a single fog cell in world units.
+const CELL_SIZE: f32 = 96.0;
+
+/// Number of fog tiles per axis around the camera.
+const TILES: i32 = 32;
+
+/// Opacity of the fog in areas that were never seen.
+const ALPHA_UNSEEN: f32 = 0.9;
+
+/// Opacity of the fog in areas that were just seen.
+const ALPHA_SEEN: f32 = 0.5;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<FogOfWar>();
+    app.init_resource::<SeenCells>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), (reset_seen_cells, spawn_fog));
+
+    app.add_systems(
+        Update,
+        (update_seen_cells, update_fog_tiles, hide_sleeping_enemies)
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update),
+    );
+}
+
+#[derive(Resource)]
+pub struct FogOfWar {
+    pub enabled: bool,
+    /// The player can see everything within this distance.
+    pub vision_radius: f32,
+    /// Time after which a seen area is completely covered by fog again.
+    pub memory: Duration,
+}
+
+impl Default for FogOfWar {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            vision_radius: 320.0,
+            memory: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Time at which each cell was last seen by the player.
+#[derive(Resource, Default)]
+struct SeenCells(HashMap<IVec2, Duration>);
+
+#[derive(Component)]
+struct FogTile {
+    // offset of this tile relative to the cell the camera is in
+    offset: IVec2,
+}
+
+fn cell_of(position: Vec2) -> IVec2 {
+    (position / CELL_SIZE).floor().as_ivec2()
+}
+
+fn cell_center(cell: IVec2) -> Vec2 {
+    (cell.as_vec2() + 0.5) * CELL_SIZE
+}
+
+fn reset_seen_cells(mut seen: ResMut<SeenCells>) {
+    seen.0.clear();
+}
+
+fn spawn_fog(mut commands: Commands, assets: Res<game::Assets>) {
+    for y in -TILES / 2..TILES / 2 {
+        for x in -TILES / 2..TILES / 2 {
+            commands.spawn((
+                Name::new("Fog"),
+                StateScoped(Screen::Gameplay),
+                FogTile {
+                    offset: ivec2(x, y),
+                },
+                Sprite {
+                    image: assets.square.clone(),
+                    // overlap a little to avoid seams between the tiles
+                    custom_size: Some(Vec2::splat(CELL_SIZE + 1.0)),
+                    color: Color::BLACK.with_alpha(ALPHA_UNSEEN),
+                    anchor: Anchor::Center,
+                    ..default()
+                },
+                Transform::from_xyz(0.0, 0.0, 2.0),
+            ));
+        }
+    }
+}
+
+fn update_seen_cells(
+    fog: Res<FogOfWar>,
+    mut seen: ResMut<SeenCells>,
+    time: Res<Time<Virtual>>,
+    player: Single<&Transform, With<Player>>,
+) {
+    let player_pos = player.translation.xy();
+    let player_cell = cell_of(player_pos);
+
+    let reach = (fog.vision_radius / CELL_SIZE).ceil() as i32;
+
+    for y in -reach..=reach {
+        for x in -reach..=reach {
+            let cell = player_cell + ivec2(x, y);
+            if cell_center(cell).distance(player_pos) <= fog.vision_radius {
+                seen.0.insert(cell, time.elapsed());
+            }
+        }
+    }
+}
+
+fn update_fog_tiles(
+    fog: Res<FogOfWar>,
+    seen: Res<SeenCells>,
+    time: Res<Time<Virtual>>,
+    player: Single<&Transform, With<Player>>,
+    camera: Single<&Transform, (With<MainCamera>, Without<FogTile>)>,
+    mut tiles: Query<(&FogTile, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+    let player_pos = player.translation.xy();
+    let camera_cell = cell_of(camera.translation.xy());
+
+    for (tile, mut transform, mut sprite, mut visibility) in &mut tiles {
+        visibility.set_if_neq(match fog.enabled {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        });
+
+        if !fog.enabled {
+            continue;
+        }
+
+        let cell = camera_cell + tile.offset;
+        let center = cell_center(cell);
+
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+
+        // how much the fog is faded in at the edge of the vision radius
+        let distance = center.distance(player_pos);
+        let outside = ((distance - fog.vision_radius + CELL_SIZE) / CELL_SIZE).clamp(0.0, 1.0);
+
+        let memory = match seen.0.get(&cell) {
+            Some(when) => {
+                let age = (time.elapsed() - *when).as_secs_f32();
+                let fraction = (age / fog.memory.as_secs_f32()).min(1.0);
+                ALPHA_SEEN + (ALPHA_UNSEEN - ALPHA_SEEN) * fraction
+            }
+
+            None => ALPHA_UNSEEN,
+        };
+
+        let alpha = outside * memory;
+        if sprite.color.alpha() != alpha {
+            sprite.color.set_alpha(alpha);
+        }
+    }
+}
+
+/// Marks an enemy that is only invisible because it sleeps within the fog.
+#[derive(Component)]
+struct HiddenByFog;
+
+fn hide_sleeping_enemies(
+    mut commands: Commands,
+    fog: Res<FogOfWar>,
+    player: Single<&Transform, With<Player>>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Visibility,
+            Has<Sleeping>,
+            Has<HiddenByFog>,
+        ),
+        With<Enemy>,
+    >,
+) {
+    let player_pos = player.translation.xy();
+
+    for (entity, transform, mut visibility, sleeping, hidden_by_fog) in &mut enemies {
+        let hidden = fog.enabled
+            && sleeping
+            && transform.translation.xy().distance(player_pos) > fog.vision_radius;
+
+        match (hidden, hidden_by_fog) {
+            (true, false) => {
+                visibility.set_if_neq(Visibility::Hidden);
+                commands.entity(entity).insert(HiddenByFog);
+            }
+
+            // only reveal enemies the fog has hidden itself
+            (false, true) => {
+                visibility.set_if_neq(Visibility::Inherited);
+                commands.entity(entity).remove::<HiddenByFog>();
+            }
+
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::game::player;
+use crate::game::screens::Screen;
+use crate::{AppSystems, PausableSystems};
+use bevy::prelude::*;
+use std::time::Duration;
+
+const COLOR_FLASH: Color = Color::srgb(1.0, 1.0, 1.0);
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<HealthRules>();
+
+    app.add_systems(
+        Update,
+        damage_flash
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// How much damage the player can take before the run ends. Set by the game mode,
+/// the default is the original rule where a single hit ends the run.
+#[derive(Resource, Clone, Debug)]
+pub struct HealthRules {
+    pub max_health: u32,
+    pub lives: u32,
+    /// Time after a hit during which the player can not be hit again.
+    pub invulnerability: Duration,
+    /// Speed at which the player is pushed away from the enemy on a hit.
+    pub knockback_speed: f32,
+}
+
+impl Default for HealthRules {
+    fn default() -> Self {
+        Self {
+            max_health: 1,
+            lives: 1,
+            invulnerability: Duration::from_millis(1500),
+            knockback_speed: 400.0,
+        }
+    }
+}
+
+impl HealthRules {
+    /// Returns true if the player survives at least one hit.
+    pub fn forgiving(&self) -> bool {
+        self.max_health > 1 || self.lives > 1
+    }
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+    pub lives: u32,
+}
+
+impl Health {
+    pub fn new(rules: &HealthRules) -> Self {
+        Self {
+            current: rules.max_health.max(1),
+            max: rules.max_health.max(1),
+            lives: rules.lives.max(1),
+        }
+    }
+
+    /// Takes a single point of damage and returns true if this was the last life.
+    pub fn damage(&mut self) -> bool {
+        self.current = self.current.saturating_sub(1);
+        if self.current > 0 {
+            return false;
+        }
+
+        self.lives = self.lives.saturating_sub(1);
+        if self.lives == 0 {
+            return true;
+        }
+
+        // start over with the next life
+        self.current = self.max;
+        false
+    }
+}
+
+/// Tints the player sprite for a moment after it was hit.
+#[derive(Component)]
+pub struct DamageFlash(pub Timer);
+
+fn damage_flash(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    mut entities: Query<(Entity, &mut DamageFlash, &mut Sprite)>,
+) {
+    for (entity, mut flash, mut sprite) in &mut entities {
+        let alpha = sprite.color.alpha();
+
+        if flash.0.tick(time.delta()).just_finished() {
+            sprite.color = player::COLOR.with_alpha(alpha);
+            commands.entity(entity).remove::<DamageFlash>();
+            continue;
+        }
+
+        let color = player::COLOR.mix(&COLOR_FLASH, flash.0.fraction_remaining());
+        sprite.color = color.with_alpha(alpha);
+    }
+}
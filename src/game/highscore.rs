@@ -1,38 +1,127 @@
+use crate::game::difficulty::DifficultyPreset;
+use crate::game::mode::{GameMode, Today, highscore_board};
+use crate::game::player;
+use crate::game::screens::Screen;
+use crate::game::stats::Stats;
 use bevy::app::{App, Update};
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
 use bevy::tasks::futures_lite::future;
 use bevy::tasks::{IoTaskPool, Task, block_on};
 use bevy::ui::{Node, Val};
 use serde::Deserialize;
+use std::cmp::Reverse;
 use tracing::info;
 
+/// Number of rows visible at once.
+const PAGE_SIZE: usize = 20;
+
+/// The server can not page, it always sends the complete board. Larger
+/// responses are rejected instead of parsed.
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
 pub fn plugin(app: &mut App) {
     app.add_event::<HighscoreClosed>();
     app.init_resource::<Highscore>();
     app.init_state::<HighscoreState>();
 
-    app.add_systems(OnEnter(HighscoreState::Loading), display_loading);
-
-    app.add_systems(
-        Update,
-        (exit_highscore, display_available).run_if(in_state(HighscoreState::Loading)),
-    );
+    app.add_systems(OnExit(Screen::Gameplay), close_highscore);
 
     app.add_systems(
         Update,
-        (exit_highscore, display_available).run_if(in_state(HighscoreState::Available)),
+        (
+            poll_responses,
+            select_tab_with_keys,
+            select_tab_with_buttons,
+            scroll_with_keys,
+            scroll_with_mouse_wheel,
+            exit_highscore,
+            display_highscore,
+        )
+            .chain()
+            .run_if(in_state(HighscoreState::Open)),
     );
 }
 
 #[derive(States, Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
 #[states(scoped_entities)]
-enum HighscoreState {
-    Loading,
-    Available,
+pub enum HighscoreState {
+    Open,
     #[default]
     Closed,
 }
 
+/// The tabs of the highscore overlay.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+enum Leaderboard {
+    #[default]
+    Global,
+    Seed,
+    Today,
+    /// The best runs on this device, taken from the local stats.
+    Personal,
+}
+
+impl Leaderboard {
+    const ALL: [Leaderboard; 4] = [
+        Leaderboard::Global,
+        Leaderboard::Seed,
+        Leaderboard::Today,
+        Leaderboard::Personal,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Leaderboard::Global => "global",
+            Leaderboard::Seed => "this seed",
+            Leaderboard::Today => "today",
+            Leaderboard::Personal => "personal best",
+        }
+    }
+}
+
+/// Identifies the run a highscore was reached in, used to pick the boards
+/// the score is recorded to.
+#[derive(Copy, Clone, Debug)]
+pub struct RunKey {
+    pub mode: GameMode,
+    pub seed: u64,
+    pub today: Today,
+    pub difficulty: DifficultyPreset,
+    pub dynamic: bool,
+}
+
+impl RunKey {
+    /// Name of the remote board behind the given tab, `None` for local tabs.
+    fn board(self, leaderboard: Leaderboard) -> Option<String> {
+        let global = match (self.difficulty, self.dynamic) {
+            // keeps using the boards from before the difficulty could be selected
+            (DifficultyPreset::Normal, false) => highscore_board(self.mode, self.today),
+            (preset, dynamic) => format!(
+                "{}-{}",
+                highscore_board(self.mode, self.today),
+                preset.id(dynamic)
+            ),
+        };
+
+        match leaderboard {
+            Leaderboard::Global => Some(global),
+            // only the daily challenge shares its seed between players
+            Leaderboard::Seed if self.mode == GameMode::Daily => {
+                Some(format!("{}-seed-{:016x}", global, self.seed))
+            }
+            Leaderboard::Seed => None,
+
+            // the global board of the daily challenge is already limited to a single day
+            Leaderboard::Today if self.mode == GameMode::Daily => Some(global),
+            Leaderboard::Today => Some(format!("{}-day-{}", global, self.today.0)),
+
+            Leaderboard::Personal => None,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct HighscoreItem {
     pub player: String,
@@ -41,7 +130,71 @@ struct HighscoreItem {
 
 type Response = Result<Vec<HighscoreItem>, String>;
 
+/// A single board, paged through locally as the server responds with the complete list.
+struct Board {
+    /// Sorted by score, the entry at index `n` has rank `n + 1`.
+    entries: Vec<HighscoreItem>,
+    /// Rank of the submitted score, once it was found on the board.
+    own_rank: Option<usize>,
+    /// Rank of the topmost visible row.
+    first: usize,
+    error: Option<String>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            own_rank: None,
+            first: 1,
+            error: None,
+        }
+    }
+}
+
+impl Board {
+    fn new(mut entries: Vec<HighscoreItem>, player: &str, score: u32) -> Self {
+        entries.sort_by_key(|item| Reverse(item.score));
+
+        let own_rank = entries
+            .iter()
+            .position(|item| item.player == player && item.score == score as usize)
+            .map(|idx| idx + 1);
+
+        let mut board = Self {
+            entries,
+            own_rank,
+            ..default()
+        };
+
+        board.scroll_to_own_rank();
+        board
+    }
+
+    /// The visible rows together with their rank.
+    fn page(&self) -> impl Iterator<Item = (usize, &HighscoreItem)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| (idx + 1, item))
+            .skip(self.first - 1)
+            .take(PAGE_SIZE)
+    }
+
+    /// Moves the view so the submitted score is in the middle of the page.
+    fn scroll_to_own_rank(&mut self) {
+        let rank = self.own_rank.unwrap_or(1);
+        self.first = rank.saturating_sub(PAGE_SIZE / 2).max(1);
+    }
+
+    fn scroll_by(&mut self, rows: isize) {
+        let max_first = self.entries.len().saturating_sub(PAGE_SIZE - 1);
+        self.first = self.first.saturating_add_signed(rows).min(max_first).max(1);
+    }
+}
+
 pub struct RecordHighscore {
+    pub key: RunKey,
     pub player: String,
     pub score: u32,
 }
@@ -49,106 +202,291 @@ pub struct RecordHighscore {
 impl Command for RecordHighscore {
     fn apply(self, world: &mut World) {
         // show the highscore screen
-        world.insert_resource(NextState::Pending(HighscoreState::Loading));
+        world.insert_resource(NextState::Pending(HighscoreState::Open));
+
+        let personal = personal_board(world.resource::<Stats>(), self.key);
 
         // and post the highscore to the server
         if let Some(mut highscore) = world.get_resource_mut::<Highscore>() {
-            highscore.post(self.player, self.score);
+            highscore.submit(self.key, self.player, self.score);
+            highscore.boards.insert(Leaderboard::Personal, personal);
         }
     }
 }
 
 #[derive(Default, Resource)]
 struct Highscore {
-    // the currently running task that fetches the highscore.
-    task: Option<Task<Response>>,
+    key: Option<RunKey>,
+    player: String,
+    score: u32,
+    selected: Leaderboard,
+    // the currently running requests
+    tasks: Vec<(Leaderboard, Task<Response>)>,
+    boards: HashMap<Leaderboard, Board>,
 }
 
 impl Highscore {
-    fn take(&mut self) -> Option<Response> {
-        if let Some(mut task) = self.task.as_mut() {
-            if let Some(resp) = block_on(future::poll_once(&mut task)) {
-                // clear the task
-                self.task = None;
-
-                // return the response
-                return Some(resp);
+    fn submit(&mut self, key: RunKey, player: String, score: u32) {
+        // cancel the previous tasks
+        for (_, task) in self.tasks.drain(..) {
+            _ = task.cancel();
+        }
+
+        self.selected = Leaderboard::Global;
+        self.boards.clear();
+
+        for leaderboard in Leaderboard::ALL {
+            if let Some(board) = key.board(leaderboard) {
+                self.tasks.push((leaderboard, post(&board, &player, score)));
+                self.boards.insert(leaderboard, Board::default());
             }
         }
 
-        None
+        self.key = Some(key);
+        self.player = player;
+        self.score = score;
     }
 
-    fn post(&mut self, player: impl AsRef<str>, score: u32) {
-        if let Some(task) = self.task.take() {
-            // cancel the previous task
-            _ = task.cancel();
-        }
+    /// The tabs that have a board for the current run.
+    fn leaderboards(&self) -> Vec<Leaderboard> {
+        Leaderboard::ALL
+            .into_iter()
+            .filter(|lb| self.boards.contains_key(lb))
+            .collect()
+    }
 
-        info!(
-            "Reporting highscore {} for player {:?}",
-            score,
-            player.as_ref()
-        );
-
-        let url = url::Url::parse_with_params(
-            "https://highscore.narf.zone/games/chainscape-1/highscore",
-            &[("player", player.as_ref()), ("score", &score.to_string())],
-        );
-
-        // create the request
-        let req = ehttp::Request::post(url.unwrap(), Vec::new());
-
-        // and schedule it to be processed asynchronously
-        let task = IoTaskPool::get().spawn(async move {
-            let resp = ehttp::fetch_async(req).await;
-
-            match resp {
-                Ok(resp) if resp.ok => {
-                    info!("Got successful response, parsing highscore now");
-                    match serde_json::from_slice::<Vec<HighscoreItem>>(&resp.bytes) {
-                        Err(err) => Err(format!("Failed to parse highscore response: {:?}", err)),
-
-                        Ok(highscore) => {
-                            info!("Highscore contains {} items", highscore.len());
-                            Ok(highscore)
-                        }
-                    }
-                }
+    fn is_loading(&self, leaderboard: Leaderboard) -> bool {
+        self.tasks.iter().any(|(lb, _)| *lb == leaderboard)
+    }
+}
 
-                Ok(resp) => Err(format!(
-                    "Failed to report highscore, got status code {:?}",
-                    resp.status
-                )),
+/// Posts the score, the server responds with the complete board.
+fn post(board: &str, player: &str, score: u32) -> Task<Response> {
+    info!(
+        "Reporting highscore {} for player {:?} to {:?}",
+        score, player, board,
+    );
 
-                Err(err) => Err(format!("Failed to report highscore: {:?}", err)),
+    let url = url::Url::parse_with_params(
+        &format!("https://highscore.narf.zone/games/{}/highscore", board),
+        &[("player", player), ("score", &score.to_string())],
+    );
+
+    // create the request
+    let req = ehttp::Request::post(url.unwrap(), Vec::new());
+
+    // and schedule it to be processed asynchronously
+    IoTaskPool::get().spawn(fetch(req))
+}
+
+async fn fetch(req: ehttp::Request) -> Response {
+    let resp = ehttp::fetch_async(req).await;
+
+    match resp {
+        Ok(resp) if resp.ok && resp.bytes.len() > MAX_RESPONSE_BYTES => Err(format!(
+            "Highscore response too large, got {} bytes",
+            resp.bytes.len()
+        )),
+
+        Ok(resp) if resp.ok => {
+            info!("Got successful response, parsing highscore now");
+            match serde_json::from_slice::<Vec<HighscoreItem>>(&resp.bytes) {
+                Err(err) => Err(format!("Failed to parse highscore response: {:?}", err)),
+
+                Ok(highscore) => {
+                    info!("Highscore contains {} items", highscore.len());
+                    Ok(highscore)
+                }
             }
+        }
+
+        Ok(resp) => Err(format!(
+            "Failed to report highscore, got status code {:?}",
+            resp.status
+        )),
+
+        Err(err) => Err(format!("Failed to report highscore: {:?}", err)),
+    }
+}
+
+fn poll_responses(mut highscore: ResMut<Highscore>) {
+    let Highscore {
+        tasks,
+        boards,
+        player,
+        score,
+        ..
+    } = highscore.bypass_change_detection();
+
+    let mut arrived = false;
+
+    tasks.retain_mut(|(leaderboard, task)| {
+        let Some(response) = block_on(future::poll_once(task)) else {
+            return true;
+        };
+
+        let board = match response {
+            Ok(items) => Board::new(items, player, *score),
+            Err(err) => Board {
+                error: Some(err),
+                ..default()
+            },
+        };
+
+        boards.insert(*leaderboard, board);
+
+        arrived = true;
+        false
+    });
+
+    if arrived {
+        highscore.set_changed();
+    }
+}
+
+/// Lists the local runs of the same mode and difficulty,
+/// the most recent run counts as the submitted one.
+fn personal_board(stats: &Stats, key: RunKey) -> Board {
+    let difficulty = key.difficulty.id(key.dynamic);
+
+    let mut runs: Vec<_> = stats
+        .runs
+        .iter()
+        .enumerate()
+        .filter(|(_, run)| run.mode == key.mode.id() && run.difficulty == difficulty)
+        .collect();
+
+    runs.sort_by_key(|(_, run)| Reverse(run.score));
+
+    let latest = stats.runs.len().checked_sub(1);
+
+    let mut board = Board::default();
+
+    for (idx, (run_idx, run)) in runs.into_iter().enumerate() {
+        if Some(run_idx) == latest {
+            board.own_rank = Some(idx + 1);
+        }
+
+        board.entries.push(HighscoreItem {
+            player: format!("{} kills, {:.0}s", run.kills, run.duration_secs),
+            score: run.score as usize,
         });
+    }
+
+    board.scroll_to_own_rank();
+    board
+}
+
+/// A button in the tab bar of the highscore overlay.
+#[derive(Component)]
+struct HighscoreTab(Leaderboard);
+
+/// Root of the highscore overlay, rebuilt whenever its content changes.
+#[derive(Component)]
+struct HighscoreView;
 
-        self.task = Some(task);
+fn select_tab_with_keys(keys: Res<ButtonInput<KeyCode>>, mut highscore: ResMut<Highscore>) {
+    let leaderboards = highscore.leaderboards();
+    if leaderboards.is_empty() {
+        return;
     }
+
+    let step = if keys.just_pressed(KeyCode::ArrowRight) {
+        1
+    } else if keys.just_pressed(KeyCode::ArrowLeft) {
+        leaderboards.len() - 1
+    } else {
+        return;
+    };
+
+    let current = leaderboards
+        .iter()
+        .position(|lb| *lb == highscore.selected)
+        .unwrap_or_default();
+
+    highscore.selected = leaderboards[(current + step) % leaderboards.len()];
 }
 
-fn display_available(
-    mut commands: Commands,
+fn select_tab_with_buttons(
+    tabs: Query<(&Interaction, &HighscoreTab), Changed<Interaction>>,
     mut highscore: ResMut<Highscore>,
-    mut next_state: ResMut<NextState<HighscoreState>>,
 ) {
-    let Some(mut highscore) = highscore.take() else {
+    for (interaction, tab) in &tabs {
+        if *interaction == Interaction::Pressed && highscore.selected != tab.0 {
+            highscore.selected = tab.0;
+        }
+    }
+}
+
+fn scroll_with_keys(keys: Res<ButtonInput<KeyCode>>, mut highscore: ResMut<Highscore>) {
+    let rows = if keys.just_pressed(KeyCode::ArrowDown) {
+        1
+    } else if keys.just_pressed(KeyCode::ArrowUp) {
+        -1
+    } else if keys.just_pressed(KeyCode::PageDown) {
+        PAGE_SIZE as isize
+    } else if keys.just_pressed(KeyCode::PageUp) {
+        -(PAGE_SIZE as isize)
+    } else if keys.just_pressed(KeyCode::Home) {
+        0
+    } else {
+        return;
+    };
+
+    let selected = highscore.selected;
+    let Some(board) = highscore.boards.get_mut(&selected) else {
         return;
     };
 
-    if let Ok(highscore) = &mut highscore {
-        // sort by key descending
-        highscore.sort_by_key(|h| h.score);
-        highscore.reverse();
+    match rows {
+        // jump back to the submitted score
+        0 => board.scroll_to_own_rank(),
+        rows => board.scroll_by(rows),
     }
+}
 
-    next_state.set(HighscoreState::Available);
+fn scroll_with_mouse_wheel(
+    mut events: EventReader<MouseWheel>,
+    mut pending: Local<f32>,
+    mut highscore: ResMut<Highscore>,
+) {
+    for event in events.read() {
+        *pending -= match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 32.0,
+        };
+    }
+
+    let rows = pending.trunc();
+    if rows == 0.0 {
+        return;
+    }
+
+    *pending -= rows;
+
+    let selected = highscore.selected;
+    if let Some(board) = highscore.boards.get_mut(&selected) {
+        board.scroll_by(rows as isize);
+    }
+}
+
+fn display_highscore(
+    mut commands: Commands,
+    highscore: Res<Highscore>,
+    views: Query<Entity, With<HighscoreView>>,
+) {
+    if !highscore.is_changed() && !views.is_empty() {
+        return;
+    }
+
+    for view in &views {
+        commands.entity(view).despawn();
+    }
 
     commands
         .spawn((
-            StateScoped(HighscoreState::Available),
+            HighscoreView,
+            StateScoped(HighscoreState::Open),
             Node {
                 width: Val::Percent(100.0),
                 height: Val::Percent(100.0),
@@ -161,7 +499,7 @@ fn display_available(
             parent
                 .spawn((Node {
                     width: Val::Percent(100.0),
-                    max_width: Val::Px(320.0),
+                    max_width: Val::Px(480.0),
                     height: Val::Percent(100.0),
                     flex_direction: FlexDirection::Column,
                     justify_content: JustifyContent::Start,
@@ -179,69 +517,95 @@ fn display_available(
                         },
                     ));
 
-                    if let Ok(entries) = &highscore {
-                        for entry in entries.iter().take(20) {
-                            parent
-                                .spawn(Node {
+                    // the tab bar
+                    parent
+                        .spawn(Node {
+                            width: Val::Percent(100.0),
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(16.0),
+                            margin: UiRect::bottom(Val::Px(16.0)),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for leaderboard in highscore.leaderboards() {
+                                let alpha = match leaderboard == highscore.selected {
+                                    true => 1.0,
+                                    false => 0.4,
+                                };
+
+                                parent.spawn((
+                                    Button,
+                                    HighscoreTab(leaderboard),
+                                    Text::new(leaderboard.label()),
+                                    TextFont::from_font_size(16.0),
+                                    TextColor(Color::srgba(1.0, 1.0, 1.0, alpha)),
+                                ));
+                            }
+                        });
+
+                    let board = highscore.boards.get(&highscore.selected);
+
+                    if let Some(rank) = board.and_then(|board| board.own_rank) {
+                        parent.spawn((
+                            Text::new(format!("your rank: #{}", rank)),
+                            TextFont::from_font_size(16.0),
+                            Node {
+                                margin: UiRect::bottom(Val::Px(8.0)),
+                                ..default()
+                            },
+                        ));
+                    }
+
+                    let Some(board) = board else {
+                        parent.spawn(Text::new("Highscore not available"));
+                        return;
+                    };
+
+                    if board.error.is_some() {
+                        parent.spawn(Text::new("Highscore not available"));
+                        return;
+                    }
+
+                    for (rank, entry) in board.page() {
+                        let background = match board.own_rank == Some(rank) {
+                            true => player::COLOR.with_alpha(0.4),
+                            false => Color::NONE,
+                        };
+
+                        parent
+                            .spawn((
+                                Node {
                                     width: Val::Percent(100.0),
                                     flex_direction: FlexDirection::Row,
+                                    padding: UiRect::horizontal(Val::Px(4.0)),
                                     ..default()
-                                })
-                                .with_children(|parent| {
-                                    parent.spawn((
-                                        Text::new(&entry.player),
-                                        Node {
-                                            flex_grow: 1.0,
-                                            ..default()
-                                        },
-                                    ));
-
-                                    parent.spawn((Text::new(entry.score.to_string()),));
-                                });
-                        }
+                                },
+                                BackgroundColor(background),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(format!("#{}", rank)),
+                                    Node {
+                                        width: Val::Px(64.0),
+                                        ..default()
+                                    },
+                                ));
+
+                                parent.spawn((
+                                    Text::new(entry.player.clone()),
+                                    Node {
+                                        flex_grow: 1.0,
+                                        ..default()
+                                    },
+                                ));
+
+                                parent.spawn((Text::new(entry.score.to_string()),));
+                            });
                     }
-                });
-        });
-}
-
-fn display_loading(mut commands: Commands) {
-    commands
-        .spawn((
-            StateScoped(HighscoreState::Loading),
-            Node {
-                width: Val::Percent(100.0),
-                height: Val::Percent(100.0),
-                flex_direction: FlexDirection::Column,
-                ..default()
-            },
-            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn((Node {
-                    width: Val::Percent(100.0),
-                    max_width: Val::Px(320.0),
-                    height: Val::Percent(100.0),
-                    flex_direction: FlexDirection::Column,
-                    justify_content: JustifyContent::Start,
-                    align_self: AlignSelf::Center,
-                    margin: UiRect::px(32.0, 32.0, 32.0, 0.0),
-                    ..default()
-                },))
-                .with_children(|parent| {
-                    parent.spawn((
-                        // the title
-                        Text::new("Highscore"),
-                        Node {
-                            margin: UiRect::bottom(Val::Px(16.0)),
-                            ..Default::default()
-                        },
-                    ));
 
-                    parent.spawn((
-                        // the title
-                        Text::new("Loading..."),
-                    ));
+                    if highscore.is_loading(highscore.selected) {
+                        parent.spawn(Text::new("Loading..."));
+                    }
                 });
         });
 }
@@ -251,7 +615,16 @@ fn exit_highscore(
     mut events: EventWriter<HighscoreClosed>,
     buttons: Res<ButtonInput<MouseButton>>,
     touches: Res<Touches>,
+    tabs: Query<&Interaction, With<HighscoreTab>>,
 ) {
+    // clicks on the tab bar only switch the tab
+    if tabs
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+
     if buttons.get_just_pressed().next().is_some() {
         state.set(HighscoreState::Closed);
         events.write(HighscoreClosed);
@@ -266,3 +639,7 @@ fn exit_highscore(
 
 #[derive(Event)]
 pub struct HighscoreClosed;
+
+fn close_highscore(mut state: ResMut<NextState<HighscoreState>>) {
+    state.set(HighscoreState::Closed);
+}
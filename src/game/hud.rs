@@ -1,6 +1,11 @@
+use crate::game::dash::Dash;
 use crate::game::enemy::{Awake, Enemy};
+use crate::game::health::{Health, HealthRules};
+use crate::game::mode::{ModeRules, Scoring};
 use crate::game::player::Player;
 use crate::game::screens::Screen;
+use crate::game::status::{StatusEffects, StatusKind};
+use crate::game::waves::WaveDirector;
 use bevy::math::FloatPow;
 use bevy::prelude::*;
 use bevy::sprite::Anchor;
@@ -13,6 +18,7 @@ pub fn plugin(app: &mut App) {
         Update,
         (
             update_hud,
+            update_status_icons,
             add_score_animation_spawn,
             add_score_animation.after(add_score_animation_spawn),
         )
@@ -23,16 +29,28 @@ pub fn plugin(app: &mut App) {
 #[derive(Component)]
 enum Hud {
     Score,
+    Health,
+    Dash,
     Stats,
+    Wave,
+    /// Remaining time of the status effect.
+    Status(StatusKind),
 }
 
+/// Icon and countdown of a status effect, hidden while the effect is not active.
+#[derive(Component)]
+struct StatusIcon(StatusKind);
+
+/// Size of the framed symbol of a status effect in pixels.
+const STATUS_ICON_SIZE: f32 = 24.0;
+
 #[derive(Event)]
 pub struct AddScore {
     pub score: u32,
     pub position: Vec2,
 }
 
-fn spawn(mut commands: Commands) {
+fn spawn(mut commands: Commands, health_rules: Res<HealthRules>, rules: Res<ModeRules>) {
     commands.spawn((
         StateScoped(Screen::Gameplay),
         Text::new("Score"),
@@ -45,6 +63,41 @@ fn spawn(mut commands: Commands) {
         },
     ));
 
+    // only relevant if the player can survive a hit at all
+    if health_rules.forgiving() {
+        commands.spawn((
+            StateScoped(Screen::Gameplay),
+            Text::new("Health"),
+            Hud::Health,
+            Node {
+                align_self: AlignSelf::Start,
+                justify_self: JustifySelf::End,
+                margin: UiRect {
+                    top: Val::Px(48.0),
+                    right: Val::Px(16.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+
+    commands.spawn((
+        StateScoped(Screen::Gameplay),
+        Text::new("Dash"),
+        Hud::Dash,
+        Node {
+            align_self: AlignSelf::End,
+            justify_self: JustifySelf::End,
+            margin: UiRect {
+                bottom: Val::Px(48.0),
+                right: Val::Px(16.0),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+
     commands.spawn((
         StateScoped(Screen::Gameplay),
         Text::new("Stats"),
@@ -56,29 +109,138 @@ fn spawn(mut commands: Commands) {
             ..default()
         },
     ));
+
+    if rules.waves {
+        commands.spawn((
+            StateScoped(Screen::Gameplay),
+            Text::new("Wave"),
+            Hud::Wave,
+            Node {
+                align_self: AlignSelf::Start,
+                justify_self: JustifySelf::Center,
+                margin: UiRect::all(Val::Px(16.0)),
+                ..default()
+            },
+        ));
+    }
+
+    // one icon per status effect, hidden while the effect is not active
+    commands
+        .spawn((
+            StateScoped(Screen::Gameplay),
+            Node {
+                align_self: AlignSelf::End,
+                justify_self: JustifySelf::Start,
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::all(Val::Px(16.0)),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            for kind in StatusKind::ALL {
+                parent.spawn((
+                    StatusIcon(kind),
+                    Visibility::Hidden,
+                    Node {
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(8.0),
+                        ..default()
+                    },
+                    children![
+                        (
+                            Node {
+                                width: Val::Px(STATUS_ICON_SIZE),
+                                height: Val::Px(STATUS_ICON_SIZE),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                border: UiRect::all(Val::Px(2.0)),
+                                ..default()
+                            },
+                            BorderColor(kind.color()),
+                            children![(
+                                Text::new(kind.glyph()),
+                                TextFont::from_font_size(14.0),
+                                TextColor(kind.color()),
+                            )],
+                        ),
+                        (Text::default(), TextColor(kind.color()), Hud::Status(kind)),
+                    ],
+                ));
+            }
+        });
 }
 
 fn update_hud(
     time: Res<Time<Virtual>>,
-    player: Single<&Player>,
+    player: Single<(&Player, &Health, &Dash, &StatusEffects)>,
     labels: Query<(&mut Text, &Hud)>,
     enemies_awake: Query<(), (With<Enemy>, With<Awake>)>,
+    director: Option<Res<WaveDirector>>,
 ) {
+    let (player, health, dash, effects) = *player;
+
     for (mut text, hud) in labels {
         text.set_if_neq(Text::new(match hud {
-            Hud::Score => {
-                format!("score: {}", player.score(time.elapsed()))
+            Hud::Score => match player.scoring {
+                Scoring::Survival => format!("score: {}", player.score(time.elapsed())),
+                Scoring::TimeAttack { .. } => {
+                    format!("time: {:.1}s", player.age(time.elapsed()).as_secs_f32())
+                }
+            },
+
+            Hud::Health => {
+                format!(
+                    "health: {}/{}, lives: {}",
+                    health.current, health.max, health.lives
+                )
             }
 
+            Hud::Dash => match dash.is_ready() {
+                true => "dash: ready".to_string(),
+                false => format!("dash: {:.1}s", dash.cooldown.remaining_secs()),
+            },
+
             Hud::Stats => {
                 let awake = enemies_awake.iter().count();
                 let killed = player.kill_count;
                 format!("awake: {}, killed: {}", awake, killed)
             }
+
+            Hud::Wave => {
+                let Some(director) = &director else {
+                    continue;
+                };
+
+                format!(
+                    "wave: {}, next in {:.0}s",
+                    director.wave,
+                    director.next.remaining_secs()
+                )
+            }
+
+            Hud::Status(kind) => {
+                let Some(effect) = effects.get(*kind) else {
+                    continue;
+                };
+
+                format!("{:.1}s", effect.timer.remaining_secs())
+            }
         }));
     }
 }
 
+fn update_status_icons(
+    effects: Single<&StatusEffects, With<Player>>,
+    mut icons: Query<(&mut Visibility, &StatusIcon)>,
+) {
+    for (mut visibility, icon) in &mut icons {
+        visibility.set_if_neq(match effects.has(icon.0) {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        });
+    }
+}
+
 #[derive(Component)]
 struct AddScoreText {
     lifetime: Timer,
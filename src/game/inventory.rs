@@ -0,0 +1,229 @@
+use crate::game::powerup::{ApplyPowerup, Powerup, PowerupDefinitions};
+use crate::game::screens::Screen;
+use crate::{AppSystems, PausableSystems};
+use bevy::prelude::*;
+
+/// Number of powerups the player can carry around.
+pub const SLOTS: usize = 3;
+
+/// Size of a single slot in the HUD in pixels.
+const SLOT_SIZE: f32 = 56.0;
+
+const ACTIVATION_KEYS: [[KeyCode; 2]; SLOTS] = [
+    [KeyCode::Digit1, KeyCode::Numpad1],
+    [KeyCode::Digit2, KeyCode::Numpad2],
+    [KeyCode::Digit3, KeyCode::Numpad3],
+];
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Inventory>();
+    app.add_event::<ActivatePowerup>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (clear_inventory, spawn_inventory),
+    );
+
+    app.add_systems(
+        Update,
+        (
+            toggle_inventory.in_set(AppSystems::RecordInput),
+            (activate_with_keys, activate_with_buttons)
+                .in_set(AppSystems::RecordInput)
+                .in_set(PausableSystems),
+            sync_inventory
+                .in_set(AppSystems::Update)
+                .run_if(resource_exists::<PowerupDefinitions>),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+
+    app.add_systems(
+        FixedUpdate,
+        apply_activated_powerups
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// Asks to apply the powerup stored in the given slot.
+#[derive(Event)]
+pub struct ActivatePowerup {
+    pub slot: usize,
+}
+
+/// Powerups collected by the player while the inventory is enabled. They are only applied
+/// once the player activates them.
+#[derive(Resource, Default)]
+pub struct Inventory {
+    pub enabled: bool,
+    slots: [Option<Powerup>; SLOTS],
+}
+
+impl Inventory {
+    /// Puts the powerup into a free slot. Returns false if the powerup
+    /// could not be stored and should be applied right away.
+    pub fn store(&mut self, powerup: Powerup) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        let Some(slot) = self.slots.iter_mut().find(|slot| slot.is_none()) else {
+            return false;
+        };
+
+        *slot = Some(powerup);
+        true
+    }
+
+    fn take(&mut self, index: usize) -> Option<Powerup> {
+        self.slots.get_mut(index)?.take()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+}
+
+#[derive(Component)]
+struct InventoryBar;
+
+#[derive(Component)]
+struct InventorySlot(usize);
+
+#[derive(Component)]
+struct InventorySlotIcon(usize);
+
+fn clear_inventory(mut inventory: ResMut<Inventory>) {
+    inventory.slots = default();
+}
+
+fn spawn_inventory(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Inventory"),
+            StateScoped(Screen::Gameplay),
+            InventoryBar,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(16.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(8.0),
+                ..default()
+            },
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            for index in 0..SLOTS {
+                parent
+                    .spawn((
+                        Button,
+                        InventorySlot(index),
+                        Node {
+                            width: Val::Px(SLOT_SIZE),
+                            height: Val::Px(SLOT_SIZE),
+                            border: UiRect::all(Val::Px(2.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderColor(Color::srgba(1.0, 1.0, 1.0, 0.5)),
+                        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            InventorySlotIcon(index),
+                            ImageNode::default(),
+                            Node {
+                                width: Val::Px(SLOT_SIZE - 16.0),
+                                height: Val::Px(SLOT_SIZE - 16.0),
+                                ..default()
+                            },
+                            Visibility::Hidden,
+                        ));
+
+                        parent.spawn((
+                            Text::new(format!("{}", index + 1)),
+                            TextFont::from_font_size(12.0),
+                            Node {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(4.0),
+                                top: Val::Px(2.0),
+                                ..default()
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+fn toggle_inventory(keys: Res<ButtonInput<KeyCode>>, mut inventory: ResMut<Inventory>) {
+    if keys.just_pressed(KeyCode::KeyI) {
+        inventory.enabled = !inventory.enabled;
+        info!("Inventory enabled: {}", inventory.enabled);
+    }
+}
+
+fn activate_with_keys(keys: Res<ButtonInput<KeyCode>>, mut activate: EventWriter<ActivatePowerup>) {
+    for (index, keys_for_slot) in ACTIVATION_KEYS.into_iter().enumerate() {
+        if keys.any_just_pressed(keys_for_slot) {
+            activate.write(ActivatePowerup { slot: index });
+        }
+    }
+}
+
+fn activate_with_buttons(
+    mut activate: EventWriter<ActivatePowerup>,
+    slots: Query<(&Interaction, &InventorySlot), Changed<Interaction>>,
+) {
+    for (interaction, slot) in &slots {
+        if *interaction == Interaction::Pressed {
+            activate.write(ActivatePowerup { slot: slot.0 });
+        }
+    }
+}
+
+/// Applies the powerups in the fixed timestep, so they act on the simulated world.
+fn apply_activated_powerups(
+    mut commands: Commands,
+    mut events: EventReader<ActivatePowerup>,
+    mut inventory: ResMut<Inventory>,
+) {
+    for event in events.read() {
+        if let Some(powerup) = inventory.take(event.slot) {
+            commands.queue(ApplyPowerup(powerup));
+        }
+    }
+}
+
+fn sync_inventory(
+    inventory: Res<Inventory>,
+    definitions: Res<PowerupDefinitions>,
+    mut bar: Query<&mut Visibility, With<InventoryBar>>,
+    mut icons: Query<(&InventorySlotIcon, &mut ImageNode, &mut Visibility), Without<InventoryBar>>,
+) {
+    if !inventory.is_changed() {
+        return;
+    }
+
+    // keep showing stored powerups after the inventory was disabled
+    for mut visibility in &mut bar {
+        visibility.set_if_neq(match inventory.enabled || !inventory.is_empty() {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        });
+    }
+
+    for (icon, mut image, mut visibility) in &mut icons {
+        let Some(definition) = inventory.slots[icon.0].and_then(|p| definitions.get(p)) else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+
+        image.image = definition.sprite.clone();
+        image.color = definition.tint;
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::game::enemy::{Archetype, Enemy, Sleeping, enemy_bundle};
+use crate::game::player::Player;
+use crate::game::screens::Screen;
+use crate::{AppSystems, game};
+use avian2d::prelude::MaxLinearSpeed;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// Size of a cell of the grid the dormant enemies are stored in.
+const CELL_SIZE: f32 = 256.0;
+
+/// Dormant enemies closer than this to the visible area become entities.
+const MATERIALIZE_MARGIN: f32 = 640.0;
+
+/// Extra distance a sleeping entity needs before it becomes dormant again,
+/// so enemies at the border do not flip back and forth.
+const HYSTERESIS: f32 = 256.0;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<DormantEnemies>();
+
+    app.add_systems(
+        FixedUpdate,
+        (dematerialize_distant, materialize_nearby)
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update),
+    );
+}
+
+/// A sleeping enemy far away from the player, kept as plain data without any
+/// entity or physics body.
+#[derive(Clone, Debug)]
+pub struct DormantEnemy {
+    pub position: Vec2,
+    pub archetype: Archetype,
+    pub max_speed: f32,
+    pub asleep_since: Duration,
+}
+
+/// All dormant enemies, bucketed by position so the ones near the player are found quickly.
+#[derive(Resource, Default)]
+pub struct DormantEnemies {
+    cells: HashMap<IVec2, Vec<DormantEnemy>>,
+}
+
+impl FromIterator<DormantEnemy> for DormantEnemies {
+    fn from_iter<T: IntoIterator<Item = DormantEnemy>>(iter: T) -> Self {
+        let mut dormant = Self::default();
+        for enemy in iter {
+            dormant.insert(enemy);
+        }
+
+        dormant
+    }
+}
+
+impl DormantEnemies {
+    fn cell(position: Vec2) -> IVec2 {
+        (position / CELL_SIZE).floor().as_ivec2()
+    }
+
+    /// All cells that may contain positions within the radius.
+    fn cells_within(center: Vec2, radius: f32) -> impl Iterator<Item = IVec2> {
+        let min = Self::cell(center - Vec2::splat(radius));
+        let max = Self::cell(center + Vec2::splat(radius));
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| ivec2(x, y)))
+    }
+
+    pub fn insert(&mut self, enemy: DormantEnemy) {
+        let cell = Self::cell(enemy.position);
+        self.cells.entry(cell).or_default().push(enemy);
+    }
+
+    pub fn within(&self, center: Vec2, radius: f32) -> impl Iterator<Item = &DormantEnemy> {
+        Self::cells_within(center, radius)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |enemy| enemy.position.distance(center) <= radius)
+    }
+
+    /// Removes and returns all dormant enemies within the radius.
+    pub fn take_within(&mut self, center: Vec2, radius: f32) -> Vec<DormantEnemy> {
+        let mut taken = Vec::new();
+
+        for cell in Self::cells_within(center, radius) {
+            let Some(enemies) = self.cells.get_mut(&cell) else {
+                continue;
+            };
+
+            let (near, far): (Vec<_>, Vec<_>) = enemies
+                .drain(..)
+                .partition(|enemy| enemy.position.distance(center) <= radius);
+
+            *enemies = far;
+            taken.extend(near);
+        }
+
+        taken
+    }
+}
+
+fn materialize_nearby(
+    mut commands: Commands,
+    mut dormant: ResMut<DormantEnemies>,
+    assets: Res<game::Assets>,
+    player: Single<&Transform, With<Player>>,
+) {
+    let center = player.translation.xy();
+    let radius = game::VIEW_RADIUS + MATERIALIZE_MARGIN;
+
+    for enemy in dormant.take_within(center, radius) {
+        commands.spawn((
+            Name::new("Enemy"),
+            StateScoped(Screen::Gameplay),
+            enemy_bundle(
+                &assets,
+                enemy.archetype,
+                enemy.max_speed,
+                enemy.asleep_since,
+            ),
+            Transform::from_translation(enemy.position.extend(1.0)),
+        ));
+    }
+}
+
+fn dematerialize_distant(
+    mut commands: Commands,
+    mut dormant: ResMut<DormantEnemies>,
+    player: Single<&Transform, With<Player>>,
+    enemies: Query<(Entity, &Transform, &Archetype, &Sleeping, &MaxLinearSpeed), With<Enemy>>,
+) {
+    let center = player.translation.xy();
+    let radius = game::VIEW_RADIUS + MATERIALIZE_MARGIN + HYSTERESIS;
+
+    for (entity, transform, archetype, sleeping, max_speed) in &enemies {
+        let position = transform.translation.xy();
+        if position.distance(center) <= radius {
+            continue;
+        }
+
+        dormant.insert(DormantEnemy {
+            position,
+            archetype: *archetype,
+            max_speed: max_speed.0,
+            asleep_since: sleeping.when,
+        });
+
+        commands.entity(entity).despawn();
+    }
+}
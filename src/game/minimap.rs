@@ -0,0 +1,325 @@
+use crate::game;
+use crate::game::ARENA_RADIUS;
+use crate::game::enemy::{Awake, Enemy};
+use crate::game::player::{self, Player};
+use crate::game::powerup::Powerup;
+use crate::game::safezone::{self, Safezone};
+use crate::game::screens::Screen;
+use bevy::prelude::*;
+use bevy::ui::{Node, Val};
+
+/// Size of the minimap on screen in pixels.
+const SIZE: f32 = 160.0;
+
+/// Number of cells per axis for the heat map and the fog.
+const GRID: usize = 24;
+
+/// Distance around the player that counts as explored.
+const EXPLORE_RADIUS: f32 = 384.0;
+
+/// Number of awake enemies in a cell at which the heat map is fully saturated.
+const HEAT_SATURATION: f32 = 8.0;
+
+const COLOR_FOG: Color = Color::srgba(0.05, 0.05, 0.05, 0.85);
+const COLOR_HEAT: Color = Color::oklcha(0.668, 0.224, 36.99, 0.8);
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<MinimapSettings>();
+    app.init_resource::<Explored>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), (reset_explored, spawn_minimap));
+
+    app.add_systems(
+        Update,
+        (
+            toggle_minimap,
+            update_explored,
+            spawn_icons,
+            update_cells,
+            update_icons,
+            update_label,
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay).and(resource_exists::<game::Assets>)),
+    );
+}
+
+#[derive(Resource)]
+pub struct MinimapSettings {
+    pub visible: bool,
+    /// Hide everything in regions the player has not visited yet.
+    pub fog: bool,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            fog: true,
+        }
+    }
+}
+
+/// Cells of the minimap the player has already been close to.
+#[derive(Resource)]
+struct Explored(Vec<bool>);
+
+impl Default for Explored {
+    fn default() -> Self {
+        Self(vec![false; GRID * GRID])
+    }
+}
+
+#[derive(Component)]
+struct Minimap;
+
+#[derive(Component)]
+struct MinimapCell(usize);
+
+#[derive(Component)]
+struct MinimapLabel;
+
+/// An icon on the minimap that follows some entity in the world.
+#[derive(Component)]
+struct MinimapIcon {
+    target: Entity,
+    size: f32,
+    /// Keep showing the icon even if the target is covered by fog.
+    always_visible: bool,
+}
+
+/// Converts a world position into a position on the minimap in pixels.
+fn to_minimap(position: Vec2) -> Vec2 {
+    let normalized = position / ARENA_RADIUS * 0.5;
+    vec2(0.5 + normalized.x, 0.5 - normalized.y) * SIZE
+}
+
+/// Returns the index of the cell containing the given world position.
+fn cell_index(position: Vec2) -> Option<usize> {
+    let cell = (to_minimap(position) / SIZE * GRID as f32).floor();
+    if cell.x < 0.0 || cell.y < 0.0 || cell.x >= GRID as f32 || cell.y >= GRID as f32 {
+        return None;
+    }
+
+    Some(cell.y as usize * GRID + cell.x as usize)
+}
+
+/// Center of the given cell in world coordinates.
+fn cell_center(index: usize) -> Vec2 {
+    let cell_size = 2.0 * ARENA_RADIUS / GRID as f32;
+    let (x, y) = ((index % GRID) as f32, (index / GRID) as f32);
+    vec2(
+        -ARENA_RADIUS + (x + 0.5) * cell_size,
+        ARENA_RADIUS - (y + 0.5) * cell_size,
+    )
+}
+
+fn reset_explored(mut explored: ResMut<Explored>) {
+    *explored = Explored::default();
+}
+
+fn spawn_minimap(mut commands: Commands, assets: Res<game::Assets>) {
+    let cell_size = SIZE / GRID as f32;
+
+    commands
+        .spawn((
+            Name::new("Minimap"),
+            StateScoped(Screen::Gameplay),
+            Minimap,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(16.0),
+                top: Val::Px(16.0),
+                width: Val::Px(SIZE),
+                height: Val::Px(SIZE),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            // the arena itself
+            parent.spawn((
+                ImageNode::new(assets.circle.clone()).with_color(Color::srgba(0.2, 0.2, 0.2, 0.75)),
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+            ));
+
+            // cells for the heat map and the fog, only within the arena
+            for index in 0..GRID * GRID {
+                if cell_center(index).length() > ARENA_RADIUS {
+                    continue;
+                }
+
+                parent.spawn((
+                    MinimapCell(index),
+                    BackgroundColor(Color::NONE),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px((index % GRID) as f32 * cell_size),
+                        top: Val::Px((index / GRID) as f32 * cell_size),
+                        width: Val::Px(cell_size),
+                        height: Val::Px(cell_size),
+                        ..default()
+                    },
+                ));
+            }
+
+            parent.spawn((
+                MinimapLabel,
+                Text::new(""),
+                TextFont::from_font_size(12.0),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(SIZE + 4.0),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn toggle_minimap(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<MinimapSettings>,
+    mut minimap: Query<&mut Visibility, With<Minimap>>,
+) {
+    if keys.just_pressed(KeyCode::KeyM) {
+        settings.visible = !settings.visible;
+    }
+
+    for mut visibility in &mut minimap {
+        visibility.set_if_neq(match settings.visible {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        });
+    }
+}
+
+fn update_explored(mut explored: ResMut<Explored>, player: Single<&Transform, With<Player>>) {
+    let player_pos = player.translation.xy();
+
+    for index in 0..GRID * GRID {
+        if explored.0[index] {
+            continue;
+        }
+
+        if cell_center(index).distance(player_pos) <= EXPLORE_RADIUS {
+            explored.0[index] = true;
+        }
+    }
+}
+
+fn spawn_icons(
+    mut commands: Commands,
+    minimap: Single<Entity, With<Minimap>>,
+    players: Query<Entity, Added<Player>>,
+    safezones: Query<Entity, Added<Safezone>>,
+    powerups: Query<Entity, Added<Powerup>>,
+) {
+    let icons = players
+        .iter()
+        .map(|target| (target, player::COLOR, 6.0, true))
+        .chain(
+            safezones
+                .iter()
+                .map(|target| (target, safezone::COLOR, 6.0, false)),
+        )
+        .chain(
+            powerups
+                .iter()
+                .map(|target| (target, Color::oklch(0.868, 0.174, 90.43), 3.0, false)),
+        );
+
+    for (target, color, size, always_visible) in icons {
+        let icon = commands
+            .spawn((
+                MinimapIcon {
+                    target,
+                    size,
+                    always_visible,
+                },
+                BackgroundColor(color),
+                BorderRadius::MAX,
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(size),
+                    height: Val::Px(size),
+                    ..default()
+                },
+            ))
+            .id();
+
+        commands.entity(*minimap).add_child(icon);
+    }
+}
+
+fn update_cells(
+    settings: Res<MinimapSettings>,
+    explored: Res<Explored>,
+    enemies: Query<&Transform, (With<Enemy>, With<Awake>)>,
+    mut cells: Query<(&MinimapCell, &mut BackgroundColor)>,
+) {
+    let mut heat = vec![0u32; GRID * GRID];
+    for transform in &enemies {
+        if let Some(index) = cell_index(transform.translation.xy()) {
+            heat[index] += 1;
+        }
+    }
+
+    for (cell, mut color) in &mut cells {
+        let new_color = if settings.fog && !explored.0[cell.0] {
+            COLOR_FOG
+        } else if heat[cell.0] > 0 {
+            let amount = (heat[cell.0] as f32 / HEAT_SATURATION).min(1.0);
+            COLOR_HEAT.with_alpha(COLOR_HEAT.alpha() * amount)
+        } else {
+            Color::NONE
+        };
+
+        color.set_if_neq(BackgroundColor(new_color));
+    }
+}
+
+fn update_icons(
+    mut commands: Commands,
+    settings: Res<MinimapSettings>,
+    explored: Res<Explored>,
+    targets: Query<&Transform>,
+    mut icons: Query<(Entity, &MinimapIcon, &mut Node, &mut Visibility)>,
+) {
+    for (entity, icon, mut node, mut visibility) in &mut icons {
+        let Ok(transform) = targets.get(icon.target) else {
+            // the target is gone, e.g. a collected powerup
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        let position = transform.translation.xy();
+
+        let hidden_by_fog = settings.fog
+            && !icon.always_visible
+            && !cell_index(position).is_some_and(|index| explored.0[index]);
+
+        visibility.set_if_neq(match hidden_by_fog {
+            true => Visibility::Hidden,
+            false => Visibility::Inherited,
+        });
+
+        let pos = to_minimap(position) - icon.size / 2.0;
+        node.left = Val::Px(pos.x);
+        node.top = Val::Px(pos.y);
+    }
+}
+
+fn update_label(
+    player: Single<&Player>,
+    powerups: Query<(), With<Powerup>>,
+    mut label: Single<&mut Text, With<MinimapLabel>>,
+) {
+    let collected = player.powerups_collected;
+    let total = collected + powerups.iter().count() as u32;
+
+    label.set_if_neq(Text::new(format!("powerups: {}/{}", collected, total)));
+}
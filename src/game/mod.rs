@@ -5,35 +5,72 @@ use bevy::prelude::*;
 use bevy::sprite::Anchor;
 use fastnoise_lite::FastNoiseLite;
 use fastnoise_lite::NoiseType;
-use ::rand::seq::IndexedRandom;
+use ::rand::Rng;
 use std::f32::consts::PI;
+use std::time::Duration;
 
 pub mod assets;
+pub mod audio;
+pub mod camera;
+pub mod clock;
 pub mod cursor;
+pub mod dash;
+pub mod decoy;
+pub mod difficulty;
 pub mod enemy;
+pub mod explosion;
+pub mod fog;
+pub mod health;
 pub mod highscore;
 mod hud;
+pub mod inventory;
+pub mod lod;
 mod markers;
+mod minimap;
+pub mod mode;
 pub mod movement;
+pub mod particles;
+pub mod perception;
 pub mod player;
 pub mod powerup;
 pub mod rand;
 pub mod safezone;
 pub mod screens;
 pub mod squishy;
-
-use crate::game::cursor::MainCamera;
-use crate::game::highscore::{HighscoreClosed, RecordHighscore};
+pub mod state_machine;
+pub mod stats;
+pub mod status;
+pub mod storage;
+pub mod tuning;
+pub mod waves;
+
+use crate::game::camera::ShakeCamera;
+use crate::game::difficulty::Difficulty;
+use crate::game::highscore::{HighscoreClosed, RecordHighscore, RunKey};
+use crate::game::lod::{DormantEnemies, DormantEnemy};
+use crate::game::mode::{GameMode, ModeRules, Today};
 use crate::game::player::Player;
-use crate::game::powerup::{powerup_bundle, Powerup};
-use crate::game::rand::{weighted_by_noise, Generate, Rand};
+use crate::game::powerup::{powerup_bundle, PowerupDefinitions};
+use crate::game::rand::{weighted_by_noise, Generate, Rand, Seed};
 use crate::game::screens::Screen;
+use crate::game::stats::{RecordRun, RunRecord};
+use crate::game::tuning::Tuning;
 use crate::Pause;
 pub use assets::Assets;
 
+/// Radius of the playable area around the origin.
+pub const ARENA_RADIUS: f32 = 4096.0;
+
+/// Distance from the player up to which gameplay treats the world as visible, e.g.
+/// for spawning and simulating enemies. Does not depend on the actual window or camera,
+/// which caps its aspect ratio and zoom so it never shows more than this.
+pub const VIEW_RADIUS: f32 = 1600.0;
+
 pub fn plugin(app: &mut App) {
     app.add_plugins((
         cursor::plugin,
+        camera::plugin,
+        decoy::plugin,
         rand::plugin,
         assets::plugin,
         screens::plugin,
@@ -44,10 +81,30 @@ pub fn plugin(app: &mut App) {
         highscore::plugin,
         powerup::plugin,
         safezone::plugin,
+        stats::plugin,
+        status::plugin,
+    ));
+
+    app.add_plugins((
         hud::plugin,
         markers::plugin,
+        minimap::plugin,
+        fog::plugin,
+        particles::plugin,
+        audio::plugin,
+        inventory::plugin,
+        explosion::plugin,
+        health::plugin,
+        dash::plugin,
+        mode::plugin,
+        difficulty::plugin,
+        tuning::plugin,
+        waves::plugin,
+        lod::plugin,
     ));
 
+    app.add_plugins(perception::plugin);
+
     app.add_systems(OnEnter(Screen::Reset), reset_to_gameplay);
     app.add_systems(
         OnEnter(Screen::Gameplay),
@@ -72,25 +129,25 @@ pub fn plugin(app: &mut App) {
 fn spawn_game(
     mut commands: Commands,
     mut rand: ResMut<Rand>,
-    mut camera: Single<&mut Transform, With<MainCamera>>,
-    time: Res<Time<Virtual>>,
+    time: Res<Time<Fixed>>,
     assets: Res<Assets>,
+    powerups: Res<PowerupDefinitions>,
+    rules: Res<ModeRules>,
+    difficulty: Res<Difficulty>,
+    tuning: Res<Tuning>,
 ) {
     commands.spawn((
         Name::new("Player"),
         StateScoped(Screen::Gameplay),
-        player::player_bundle(&time, &assets),
+        player::player_bundle(&time, &assets, &rules, &tuning),
         Transform::from_xyz(0.0, 0.0, 0.5),
     ));
 
-    camera.translation.x = 0.0;
-    camera.translation.y = 0.0;
-
-    let mut generator = Generate::new(4096.0, 256.0, Vec2::ZERO);
+    let mut generator = Generate::new(ARENA_RADIUS, 256.0, Vec2::ZERO);
 
     let random_pos = |radius| rand.vec2() * radius;
 
-    for pos in generator.generate(random_pos, 3, 128.0) {
+    for pos in generator.generate(random_pos, rules.safezones, 128.0) {
         // place the safe zone
         commands.spawn((
             Name::new("SafeZone"),
@@ -115,15 +172,17 @@ fn spawn_game(
 
     // place some random powerups with some space around them
     let random_pos = |radius| rand.vec2() * radius;
-    for pos in generator.generate(random_pos, 128, 128.0) {
-        let powerup = [Powerup::Speed, Powerup::Explosion, Powerup::Coin]
+    for pos in generator.generate(random_pos, rules.powerups, 128.0) {
+        let Some(bundle) = powerups
             .choose(&mut *rand)
-            .unwrap();
+            .and_then(|powerup| powerup_bundle(&powerups, powerup))
+        else {
+            break;
+        };
 
         commands.spawn((
-            Name::new("Powerup"),
             StateScoped(Screen::Gameplay),
-            powerup_bundle(&assets, *powerup),
+            bundle,
             Transform::from_translation(pos.extend(1.0)),
         ));
     }
@@ -133,18 +192,25 @@ fn spawn_game(
     noise.noise_type = NoiseType::Cellular;
     noise.frequency = 0.001;
 
-    for pos in generator.generate(weighted_by_noise(rand.as_mut(), noise), 4096, 32.0) {
-        commands.spawn((
-            Name::new("Enemy"),
-            StateScoped(Screen::Gameplay),
-            enemy::enemy_bundle(rand.as_mut(), &assets),
-            Transform::from_translation(pos.extend(1.0)),
-        ));
-    }
+    let positions = generator.generate(
+        weighted_by_noise(rand.as_mut(), noise),
+        difficulty.tuning.enemies,
+        32.0,
+    );
+
+    // enemies start dormant and only become entities once the player comes near
+    let enemies = positions.into_iter().map(|position| DormantEnemy {
+        position,
+        archetype: tuning.flocking.choose(rand.as_mut()),
+        max_speed: rand.random_range(difficulty.speed()),
+        asleep_since: Duration::ZERO,
+    });
+
+    commands.insert_resource(DormantEnemies::from_iter(enemies));
 }
 
 fn spawn_outer_area(mut commands: Commands, assets: Res<Assets>) {
-    let radius = 4096.0;
+    let radius = ARENA_RADIUS;
 
     const STEP_SIZE: f32 = 10.0;
 
@@ -238,18 +304,48 @@ fn game_ends_system(
     end_game: In<EndGame>,
     mut commands: Commands,
     mut time: ResMut<Time<Virtual>>,
+    fixed_time: Res<Time<Fixed>>,
+    mut shake: EventWriter<ShakeCamera>,
+    seed: Res<Seed>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    today: Res<Today>,
     mut query_player: Single<(&Player, &mut Visibility)>,
 ) {
     let (player, player_visibility) = &mut *query_player;
 
+    let score = player.score(fixed_time.elapsed());
+
+    commands.queue(RecordRun(RunRecord {
+        timestamp: clock::unix_time_secs(),
+        seed: seed.0,
+        mode: mode.id().to_string(),
+        difficulty: difficulty.preset.id(difficulty.dynamic),
+        score,
+        duration_secs: player.age(fixed_time.elapsed()).as_secs_f32(),
+        kills: player.kill_count,
+        win: end_game.win,
+        powerups: player.powerups_collected,
+    }));
+
+    // queued after the run was recorded, so the personal board already contains it
     commands.queue(RecordHighscore {
+        key: RunKey {
+            mode: *mode,
+            seed: seed.0,
+            today: *today,
+            difficulty: difficulty.preset,
+            dynamic: difficulty.dynamic,
+        },
         player: player_name(),
-        score: player.score(time.elapsed()),
+        score,
     });
 
     if !end_game.win {
         // hide the player
         player_visibility.set_if_neq(Visibility::Hidden);
+
+        shake.write(ShakeCamera { trauma: 1.0 });
     }
 
     // pause the systems
@@ -0,0 +1,157 @@
+use crate::game::clock;
+use crate::game::health::HealthRules;
+use crate::game::screens::Screen;
+use bevy::prelude::*;
+
+/// Name of the original highscore board, still used by the classic mode.
+const BOARD_PREFIX: &str = "chainscape-1";
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<GameMode>();
+    app.insert_resource(GameMode::default().rules());
+    app.init_resource::<Today>();
+
+    app.add_systems(OnEnter(Screen::Reset), apply_rules);
+}
+
+/// The kind of game the player selected in the menu.
+#[derive(Resource, Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum GameMode {
+    /// Reach a safezone or die.
+    #[default]
+    Classic,
+    /// There is no way out, survive as long as possible.
+    Endless,
+    /// Reach a safezone as fast as possible.
+    TimeAttack,
+    /// The classic mode, but everybody plays the same map each day.
+    Daily,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Classic,
+        GameMode::Endless,
+        GameMode::TimeAttack,
+        GameMode::Daily,
+    ];
+
+    /// Stable identifier, used for statistics and highscore boards.
+    pub fn id(self) -> &'static str {
+        match self {
+            GameMode::Classic => "classic",
+            GameMode::Endless => "endless",
+            GameMode::TimeAttack => "time-attack",
+            GameMode::Daily => "daily",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Endless => "Endless",
+            GameMode::TimeAttack => "Time attack",
+            GameMode::Daily => "Daily challenge",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            GameMode::Classic => "Escape to one of the safezones.",
+            GameMode::Endless => "No safezones, survive as long as you can.",
+            GameMode::TimeAttack => "Reach a safezone as fast as possible.",
+            GameMode::Daily => "Same map for everyone, new every day.",
+        }
+    }
+
+    pub fn rules(self) -> ModeRules {
+        let classic = ModeRules {
+            safezones: 3,
+            powerups: 128,
+            waves: false,
+            health: HealthRules::default(),
+            scoring: Scoring::Survival,
+        };
+
+        match self {
+            GameMode::Classic | GameMode::Daily => classic,
+
+            GameMode::Endless => ModeRules {
+                safezones: 0,
+                waves: true,
+                health: HealthRules {
+                    max_health: 3,
+                    ..default()
+                },
+                ..classic
+            },
+
+            GameMode::TimeAttack => ModeRules {
+                powerups: 64,
+                scoring: Scoring::TimeAttack { par_secs: 120 },
+                ..classic
+            },
+        }
+    }
+}
+
+/// How the score of a run is calculated.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Scoring {
+    /// Every second survived is worth a point.
+    Survival,
+    /// Reaching a safezone is worth ten points for every second below par.
+    TimeAttack { par_secs: u32 },
+}
+
+/// Configuration of a run, derived from the game mode when a new run starts.
+#[derive(Resource, Clone, Debug)]
+pub struct ModeRules {
+    pub safezones: usize,
+    pub powerups: usize,
+    /// Keeps spawning new enemies in waves during the run.
+    pub waves: bool,
+    pub health: HealthRules,
+    pub scoring: Scoring,
+}
+
+/// Number of days since the unix epoch. Fixed when a run starts so a daily run
+/// crossing midnight still counts for the day it was started on.
+#[derive(Resource, Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Today(pub u64);
+
+impl Default for Today {
+    fn default() -> Self {
+        Self(clock::unix_time_secs() / SECS_PER_DAY)
+    }
+}
+
+impl Today {
+    /// Seed of the daily challenge map.
+    pub fn seed(self) -> u64 {
+        // spread consecutive days over the whole range
+        self.0.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+    }
+}
+
+/// Name of the highscore board a run of the given mode is recorded to.
+pub fn highscore_board(mode: GameMode, today: Today) -> String {
+    match mode {
+        GameMode::Classic => BOARD_PREFIX.to_string(),
+        GameMode::Daily => format!("{}-daily-{}", BOARD_PREFIX, today.0),
+        _ => format!("{}-{}", BOARD_PREFIX, mode.id()),
+    }
+}
+
+pub fn apply_rules(
+    mode: Res<GameMode>,
+    mut rules: ResMut<ModeRules>,
+    mut health_rules: ResMut<HealthRules>,
+    mut today: ResMut<Today>,
+) {
+    *rules = mode.rules();
+    *health_rules = rules.health.clone();
+    *today = Today::default();
+}
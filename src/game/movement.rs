@@ -1,28 +1,40 @@
 use crate::{AppSystems, PausableSystems};
 use avian2d::prelude::LinearVelocity;
-use bevy::app::{App, Update};
+use bevy::app::{App, FixedUpdate};
 use bevy::math::{Quat, Vec2, Vec3Swizzles, vec3};
-use bevy::prelude::{Component, DetectChangesMut, IntoScheduleConfigs, Query, Res, Transform};
-use bevy::time::{Time, Virtual};
+use bevy::prelude::{
+    Commands, Component, DetectChangesMut, Entity, IntoScheduleConfigs, Query, Res, Timer,
+    Transform, With, Without,
+};
+use bevy::time::{Fixed, Time};
 
 pub fn plugin(app: &mut App) {
     app.add_systems(
-        Update,
-        apply_movement
+        FixedUpdate,
+        (tick_knockback, apply_movement)
+            .chain()
             .in_set(AppSystems::Update)
             .in_set(PausableSystems),
     );
 }
 
+/// Hands control over to the physics engine for a moment, so an impulse can play out.
+#[derive(Component)]
+pub struct Knockback(pub Timer);
+
 #[derive(Component)]
 pub struct Movement {
     pub target_velocity: Vec2,
     pub angular_velocity: f32,
+    /// Scales the target velocity, e.g. while a speed boost is active.
+    pub speed_multiplier: f32,
+    /// Additional multiplier for short bursts like a dash.
+    pub boost: f32,
 }
 
 fn apply_movement(
-    time: Res<Time<Virtual>>,
-    mut entities: Query<(&mut Transform, &Movement, &mut LinearVelocity)>,
+    time: Res<Time<Fixed>>,
+    mut entities: Query<(&mut Transform, &Movement, &mut LinearVelocity), Without<Knockback>>,
 ) {
     let dt = time.delta_secs();
 
@@ -36,8 +48,21 @@ fn apply_movement(
             .rotate_towards(target_quat, mov.angular_velocity * dt);
 
         let direction = transform.rotation * vec3(1.0, 0.0, 0.0);
-        let current_velocity = direction.xy() * mov.target_velocity.length();
+        let speed = mov.target_velocity.length() * mov.speed_multiplier * mov.boost;
+        let current_velocity = direction.xy() * speed;
 
         velocity.set_if_neq(LinearVelocity(current_velocity));
     }
 }
+
+fn tick_knockback(
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+    mut entities: Query<(Entity, &mut Knockback), With<Movement>>,
+) {
+    for (entity, mut knockback) in &mut entities {
+        if knockback.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).remove::<Knockback>();
+        }
+    }
+}
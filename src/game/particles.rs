@@ -0,0 +1,265 @@
+use crate::game;
+use crate::game::enemy::EnemyKilled;
+use crate::game::screens::Screen;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::f32::consts::TAU;
+use std::ops::Range;
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<EmitParticles>();
+    app.init_resource::<ParticlePool>();
+
+    app.add_systems(OnEnter(Screen::Reset), kill_all_particles);
+
+    app.add_systems(
+        Update,
+        (emit_for_killed_enemies, emit_particles, update_particles)
+            .chain()
+            .run_if(in_state(Screen::Gameplay).and(resource_exists::<game::Assets>)),
+    );
+}
+
+/// Spawns a burst of particles at the given position.
+#[derive(Event)]
+pub struct EmitParticles {
+    pub position: Vec2,
+    pub effect: ParticleEffect,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum ParticleEffect {
+    EnemyDeath { awake: bool },
+    PowerupPickup { color: Color },
+    Shockwave { radius: f32 },
+    Frost { radius: f32 },
+    SafezoneArrival,
+}
+
+/// Describes how the particles of a single burst look and behave.
+struct Emitter {
+    count: usize,
+    speed: Range<f32>,
+    lifetime: Range<f32>,
+    size: (f32, f32),
+    color: (Color, Color),
+    /// Fraction of velocity lost per second.
+    drag: f32,
+    /// Particles start on a ring with this radius instead of the center.
+    ring_radius: f32,
+}
+
+impl ParticleEffect {
+    fn emitter(self) -> Emitter {
+        match self {
+            ParticleEffect::EnemyDeath { awake } => Emitter {
+                count: if awake { 16 } else { 8 },
+                speed: 60.0..180.0,
+                lifetime: 0.3..0.6,
+                size: (8.0, 2.0),
+                color: match awake {
+                    true => (
+                        Color::oklcha(0.668, 0.224, 36.99, 0.9),
+                        Color::oklcha(0.668, 0.224, 36.99, 0.0),
+                    ),
+                    false => (
+                        Color::oklcha(0.668, 0.0, 36.99, 0.9),
+                        Color::oklcha(0.668, 0.0, 36.99, 0.0),
+                    ),
+                },
+                drag: 3.0,
+                ring_radius: 0.0,
+            },
+
+            ParticleEffect::PowerupPickup { color } => Emitter {
+                count: 20,
+                speed: 40.0..120.0,
+                lifetime: 0.4..0.8,
+                size: (6.0, 0.0),
+                color: (color, color.with_alpha(0.0)),
+                drag: 2.0,
+                ring_radius: 16.0,
+            },
+
+            ParticleEffect::Shockwave { radius } => Emitter {
+                count: 64,
+                // travel roughly to the edge of the blast during the lifetime
+                speed: radius * 1.5..radius * 2.0,
+                lifetime: 0.35..0.45,
+                size: (16.0, 4.0),
+                color: (
+                    Color::srgba(1.0, 0.9, 0.7, 0.9),
+                    Color::srgba(1.0, 0.4, 0.1, 0.0),
+                ),
+                drag: 2.5,
+                ring_radius: 8.0,
+            },
+
+            ParticleEffect::Frost { radius } => Emitter {
+                count: 48,
+                speed: radius * 1.2..radius * 1.6,
+                lifetime: 0.5..0.7,
+                size: (12.0, 2.0),
+                color: (
+                    Color::srgba(0.8, 0.95, 1.0, 0.9),
+                    Color::srgba(0.3, 0.6, 1.0, 0.0),
+                ),
+                drag: 2.0,
+                ring_radius: 8.0,
+            },
+
+            ParticleEffect::SafezoneArrival => Emitter {
+                count: 48,
+                speed: 80.0..240.0,
+                lifetime: 0.8..1.4,
+                size: (10.0, 0.0),
+                color: (game::safezone::COLOR, game::safezone::COLOR.with_alpha(0.0)),
+                drag: 1.5,
+                ring_radius: 32.0,
+            },
+        }
+    }
+}
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    drag: f32,
+    lifetime: Timer,
+    size: (f32, f32),
+    color: (Color, Color),
+}
+
+/// Keeps particle entities around after they died so they can be reused.
+#[derive(Resource)]
+struct ParticlePool {
+    free: Vec<Entity>,
+    // particles are purely cosmetic, they should not consume gameplay randomness.
+    rand: SmallRng,
+}
+
+impl Default for ParticlePool {
+    fn default() -> Self {
+        Self {
+            free: Vec::new(),
+            rand: SmallRng::seed_from_u64(0),
+        }
+    }
+}
+
+fn emit_for_killed_enemies(
+    mut killed: EventReader<EnemyKilled>,
+    mut emit: EventWriter<EmitParticles>,
+) {
+    for event in killed.read() {
+        emit.write(EmitParticles {
+            position: event.position,
+            effect: ParticleEffect::EnemyDeath { awake: event.awake },
+        });
+    }
+}
+
+fn emit_particles(
+    mut commands: Commands,
+    mut events: EventReader<EmitParticles>,
+    mut pool: ResMut<ParticlePool>,
+    assets: Res<game::Assets>,
+) {
+    for event in events.read() {
+        let emitter = event.effect.emitter();
+
+        for _ in 0..emitter.count {
+            let rand = &mut pool.rand;
+
+            let direction = Vec2::from_angle(rand.random_range(0.0..TAU));
+            let speed = rand.random_range(emitter.speed.clone());
+            let lifetime = rand.random_range(emitter.lifetime.clone());
+
+            let position = event.position + direction * emitter.ring_radius;
+
+            let particle = Particle {
+                velocity: direction * speed,
+                drag: emitter.drag,
+                lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
+                size: emitter.size,
+                color: emitter.color,
+            };
+
+            let transform = Transform::from_translation(position.extend(1.5));
+
+            // reuse a dead particle if possible
+            if let Some(entity) = pool.free.pop() {
+                commands
+                    .entity(entity)
+                    .insert((particle, transform, Visibility::Inherited));
+                continue;
+            }
+
+            commands.spawn((
+                Name::new("Particle"),
+                particle,
+                transform,
+                Sprite {
+                    image: assets.circle.clone(),
+                    anchor: Anchor::Center,
+                    custom_size: Some(Vec2::splat(emitter.size.0)),
+                    color: emitter.color.0,
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+fn update_particles(
+    // keep animating while the game is paused, e.g. after reaching a safezone
+    time: Res<Time<Real>>,
+    mut pool: ResMut<ParticlePool>,
+    mut particles: Query<(
+        Entity,
+        &mut Particle,
+        &mut Transform,
+        &mut Sprite,
+        &mut Visibility,
+    )>,
+) {
+    let dt = time.delta_secs();
+
+    for (entity, mut particle, mut transform, mut sprite, mut visibility) in &mut particles {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        if particle.lifetime.tick(time.delta()).just_finished() {
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+            continue;
+        }
+
+        let drag = (1.0 - particle.drag * dt).max(0.0);
+        particle.velocity *= drag;
+
+        transform.translation += (particle.velocity * dt).extend(0.0);
+
+        let f = particle.lifetime.fraction();
+        let (size_start, size_end) = particle.size;
+        let (color_start, color_end) = particle.color;
+
+        sprite.custom_size = Some(Vec2::splat(size_start.lerp(size_end, f)));
+        sprite.color = color_start.mix(&color_end, f);
+    }
+}
+
+fn kill_all_particles(
+    mut pool: ResMut<ParticlePool>,
+    mut particles: Query<(Entity, &mut Visibility), With<Particle>>,
+) {
+    pool.free.clear();
+
+    for (entity, mut visibility) in &mut particles {
+        *visibility = Visibility::Hidden;
+        pool.free.push(entity);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::game::difficulty::Difficulty;
+use crate::game::player::Player;
+use crate::game::screens::Screen;
+use crate::game::tuning::Tuning;
+use crate::{AppSystems, PausableSystems};
+use avian2d::prelude::{LinearVelocity, RigidBody, SpatialQuery, SpatialQueryFilter};
+use bevy::prelude::*;
+
+/// A player standing still still makes a bit of noise.
+const MIN_PLAYER_NOISE: f32 = 0.5;
+
+/// Explosions are heard this many times further than their blast radius.
+const EXPLOSION_NOISE_FACTOR: f32 = 2.0;
+
+/// Awake enemies can not see anything further away than this.
+pub const SIGHT_RANGE: f32 = 600.0;
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<Noise>();
+
+    app.add_systems(
+        FixedUpdate,
+        player_noise
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// Wakes sleeping enemies within the radius and tells awake ones where the noise came from.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct Noise {
+    pub position: Vec2,
+    pub radius: f32,
+    pub kind: NoiseKind,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NoiseKind {
+    /// The footsteps of the player, reveal its position.
+    Player,
+    /// A single loud bang.
+    Explosion,
+}
+
+impl Noise {
+    pub fn explosion(position: Vec2, blast_radius: f32) -> Self {
+        Self {
+            position,
+            radius: blast_radius * EXPLOSION_NOISE_FACTOR,
+            kind: NoiseKind::Explosion,
+        }
+    }
+
+    pub fn is_heard_at(&self, position: Vec2) -> bool {
+        self.position.distance(position) <= self.radius
+    }
+}
+
+/// The player is heard further the faster it moves, e.g. when dashing or boosted.
+fn player_noise(
+    difficulty: Res<Difficulty>,
+    tuning: Res<Tuning>,
+    player: Single<(&Transform, &LinearVelocity), With<Player>>,
+    mut noise: EventWriter<Noise>,
+) {
+    let (transform, velocity) = *player;
+
+    let loudness = (velocity.length() / tuning.player.speed).max(MIN_PLAYER_NOISE);
+
+    noise.write(Noise {
+        position: transform.translation.xy(),
+        radius: difficulty.wake_distance(true) * loudness,
+        kind: NoiseKind::Player,
+    });
+}
+
+/// Returns true if no static obstacle blocks the view between both points.
+pub fn line_of_sight(
+    spatial_query: &SpatialQuery,
+    bodies: &Query<&RigidBody>,
+    from: Vec2,
+    to: Vec2,
+) -> bool {
+    let offset = to - from;
+
+    if offset.length() > SIGHT_RANGE {
+        return false;
+    }
+
+    let Ok(direction) = Dir2::new(offset) else {
+        return true;
+    };
+
+    let is_obstacle = |entity: Entity| {
+        bodies
+            .get(entity)
+            .is_ok_and(|body| *body == RigidBody::Static)
+    };
+
+    spatial_query
+        .cast_ray_predicate(
+            from,
+            direction,
+            offset.length(),
+            true,
+            &SpatialQueryFilter::default(),
+            &is_obstacle,
+        )
+        .is_none()
+}
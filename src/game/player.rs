@@ -1,37 +1,53 @@
 use crate::game::EndGame;
+use crate::game::camera::ShakeCamera;
 use crate::game::cursor::{MainCamera, WorldCursor};
-use crate::game::enemy::{Awake, Enemy};
+use crate::game::dash::Dash;
+use crate::game::enemy::{Awake, Enemy, EnemyKilled};
+use crate::game::health::{DamageFlash, Health, HealthRules};
 use crate::game::hud::AddScore;
-use crate::game::movement::Movement;
+use crate::game::mode::{ModeRules, Scoring};
+use crate::game::movement::{Knockback, Movement};
 use crate::game::screens::Screen;
 use crate::game::squishy::Squishy;
+use crate::game::status::{StatusEffects, StatusKind};
+use crate::game::tuning::{ScoreTuning, Tuning};
 use crate::{AppSystems, PausableSystems, Pause, game};
-use avian2d::prelude::{Collider, Collisions, LinearVelocity, RigidBody};
+use avian2d::prelude::{
+    Collider, Collisions, ComputedMass, ExternalImpulse, LinearVelocity, RigidBody,
+};
 use bevy::prelude::*;
 use bevy::sprite::Anchor;
 use std::time::Duration;
 use tracing::info;
 
 pub fn plugin(app: &mut App) {
+    app.add_event::<SteerPlayer>();
+
     app.add_systems(
         Update,
+        (handle_player_input, handle_player_input_touch)
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::RecordInput)
+            .in_set(PausableSystems),
+    );
+
+    app.add_systems(
+        FixedUpdate,
         (
             handle_player_enemy_collision_awake,
             handle_player_enemy_collision_non_awake,
-            handle_player_input,
-            handle_player_input_touch,
+            steer_player,
         )
             .run_if(in_state(Screen::Gameplay))
             .in_set(AppSystems::Update)
             .in_set(PausableSystems),
     );
+}
 
-    app.add_systems(
-        PostUpdate,
-        camera_follow_player
-            .run_if(in_state(Screen::Gameplay))
-            .in_set(AppSystems::Update),
-    );
+/// Asks the player to move towards a position in the world.
+#[derive(Event)]
+pub struct SteerPlayer {
+    pub target: Vec2,
 }
 
 #[derive(Component)]
@@ -39,41 +55,97 @@ pub struct Player {
     born: Duration,
     pub safezone_reached: bool,
     pub kill_count: u32,
+    pub powerups_collected: u32,
+    pub scoring: Scoring,
+    /// Points awarded for kills and reaching a safezone.
+    pub points: ScoreTuning,
     score: u32,
 }
 
 impl Player {
+    /// Time since the player was spawned.
+    pub fn age(&self, now: Duration) -> Duration {
+        now - self.born
+    }
+
     pub fn score(&self, now: Duration) -> u32 {
-        let age = (now - self.born).as_secs() as u32;
-        let safezone = if self.safezone_reached { 100 } else { 0 };
-        age + self.score + safezone
+        let age = self.age(now).as_secs() as u32;
+
+        match self.scoring {
+            Scoring::Survival => {
+                let safezone = match self.safezone_reached {
+                    true => self.points.safezone_bonus,
+                    false => 0,
+                };
+                age + self.score + safezone
+            }
+
+            Scoring::TimeAttack { par_secs } => {
+                let safezone = match self.safezone_reached {
+                    true => {
+                        self.points.safezone_bonus
+                            + self.points.time_attack_per_second * par_secs.saturating_sub(age)
+                    }
+                    false => 0,
+                };
+
+                self.score + safezone
+            }
+        }
     }
 
     pub fn add_kill(&mut self, awake: bool) -> u32 {
         self.kill_count += 1;
-        let delta = if awake { 15 } else { 5 };
+        let delta = match awake {
+            true => self.points.kill_awake,
+            false => self.points.kill_sleeping,
+        };
         self.score += delta;
         delta
     }
 
+    /// Records a kill by a chain explosion, the score grows with every link of the chain.
+    pub fn add_chain_kill(&mut self, awake: bool, link: u32) -> u32 {
+        let delta = self.add_kill(awake);
+        delta + self.add_score(delta * link)
+    }
+
     pub fn add_score(&mut self, delta: u32) -> u32 {
         self.score += delta;
         delta
     }
 }
 
-pub fn player_bundle(time: &Time<Virtual>, assets: &game::Assets) -> impl Bundle {
+pub const COLOR: Color = Color::oklch(0.645, 0.260, 2.47);
+
+/// Radius of the collider of the player.
+pub const RADIUS: f32 = 16.0;
+
+pub fn player_bundle(
+    time: &Time<Fixed>,
+    assets: &game::Assets,
+    rules: &ModeRules,
+    tuning: &Tuning,
+) -> impl Bundle {
     (
         Player {
             born: time.elapsed(),
             kill_count: 0,
+            powerups_collected: 0,
+            scoring: rules.scoring,
+            points: tuning.score,
             score: 0,
             safezone_reached: false,
         },
         Movement {
             target_velocity: Vec2::ZERO,
-            angular_velocity: 8.0,
+            angular_velocity: tuning.player.angular_velocity,
+            speed_multiplier: 1.0,
+            boost: 1.0,
         },
+        Dash::default(),
+        StatusEffects::default(),
+        Health::new(&rules.health),
         Squishy {
             frequency: 2.0,
             scale_min: vec2(1.0, 0.9),
@@ -83,27 +155,88 @@ pub fn player_bundle(time: &Time<Virtual>, assets: &game::Assets) -> impl Bundle
         Sprite {
             image: assets.player.clone(),
             custom_size: Some(Vec2::splat(32.0)),
-            color: Color::oklch(0.645, 0.260, 2.47),
+            color: COLOR,
             anchor: Anchor::Center,
             ..default()
         },
         RigidBody::Dynamic,
-        Collider::circle(16.0),
+        Collider::circle(RADIUS),
         LinearVelocity::ZERO,
+        ExternalImpulse::default(),
     )
 }
 
+/// Grace period after the shield broke, so the rest of the horde does not kill the player
+/// in the very next frame.
+const SHIELD_GRACE: Duration = Duration::from_millis(1000);
+
+/// Time the player can not steer after being hit.
+const KNOCKBACK_DURATION: Duration = Duration::from_millis(250);
+
+const DAMAGE_FLASH_DURATION: Duration = Duration::from_millis(400);
+
+/// The player limps for a moment after being hit.
+const HIT_SLOW_DURATION: Duration = Duration::from_millis(2000);
+const HIT_SLOW_FACTOR: f32 = 0.7;
+
 fn handle_player_enemy_collision_awake(
     mut commands: Commands,
-    player: Single<Entity, With<Player>>,
-    query_enemies: Query<(), (With<Enemy>, With<Awake>)>,
+    rules: Res<HealthRules>,
+    mut player: Single<
+        (
+            Entity,
+            &Transform,
+            &mut StatusEffects,
+            &mut Health,
+            &ComputedMass,
+            &mut LinearVelocity,
+            &mut ExternalImpulse,
+        ),
+        With<Player>,
+    >,
+    query_enemies: Query<&Transform, (With<Enemy>, With<Awake>)>,
     collisions: Collisions,
+    mut shake: EventWriter<ShakeCamera>,
 ) {
+    let (player, player_transform, effects, health, mass, velocity, impulse) = &mut *player;
+
+    if effects.has(StatusKind::Invulnerable) {
+        return;
+    }
+
     for collider in collisions.entities_colliding_with(*player) {
-        if query_enemies.contains(collider) {
+        let Ok(enemy_transform) = query_enemies.get(collider) else {
+            continue;
+        };
+
+        if effects.has(StatusKind::Shield) {
+            effects.remove(StatusKind::Shield);
+            effects.add(StatusKind::Invulnerable, SHIELD_GRACE, 1.0);
+            return;
+        }
+
+        if health.damage() {
             commands.queue(EndGame { win: false });
             return;
         }
+
+        effects.add(StatusKind::Invulnerable, rules.invulnerability, 1.0);
+        effects.add(StatusKind::Slow, HIT_SLOW_DURATION, HIT_SLOW_FACTOR);
+
+        // push the player away from the enemy
+        let direction = (player_transform.translation.xy() - enemy_transform.translation.xy())
+            .normalize_or(Vec2::X);
+
+        velocity.0 = Vec2::ZERO;
+        impulse.apply_impulse(direction * rules.knockback_speed * mass.value());
+
+        commands.entity(*player).insert((
+            Knockback(Timer::new(KNOCKBACK_DURATION, TimerMode::Once)),
+            DamageFlash(Timer::new(DAMAGE_FLASH_DURATION, TimerMode::Once)),
+        ));
+
+        shake.write(ShakeCamera { trauma: 0.5 });
+        return;
     }
 }
 
@@ -112,6 +245,7 @@ fn handle_player_enemy_collision_non_awake(
     mut query_player: Single<(Entity, &mut Player)>,
     query_enemies: Query<&Transform, (With<Enemy>, Without<Awake>)>,
     mut add_score: EventWriter<AddScore>,
+    mut killed: EventWriter<EnemyKilled>,
     collisions: Collisions,
 ) {
     let (player_entity, player) = &mut *query_player;
@@ -124,38 +258,35 @@ fn handle_player_enemy_collision_non_awake(
                 score: player.add_kill(false),
             });
 
+            killed.write(EnemyKilled {
+                position: enemy_transform.translation.xy(),
+                awake: false,
+            });
+
             //  and remove it from the map
             commands.entity(collider).despawn();
         }
     }
 }
 
+/// Clicks on buttons like the inventory slots must not steer the player.
+fn is_over_ui(interactions: &Query<&Interaction>) -> bool {
+    interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+}
+
 fn handle_player_input(
     cursor: Res<WorldCursor>,
     mut unpause: ResMut<NextState<Pause>>,
     mouse_button: Res<ButtonInput<MouseButton>>,
-    mut query_player: Query<(&Transform, &mut Movement), With<Player>>,
+    mut steer: EventWriter<SteerPlayer>,
+    interactions: Query<&Interaction>,
 ) {
-    let Ok((player_transform, mut player_movement)) = query_player.single_mut() else {
-        return;
-    };
-
-    if mouse_button.just_pressed(MouseButton::Left) {
+    if mouse_button.just_pressed(MouseButton::Left) && !is_over_ui(&interactions) {
         info!("Mouse button was just pressed at {:?}", cursor.0);
 
-        let player_pos = player_transform.translation.xy();
-        let target_pos = cursor.0;
-
-        // direction the player wants to move to
-        let direction = target_pos - player_pos;
-
-        // clicked on the player itself
-        if direction.length_squared() < 0.01 {
-            return;
-        }
-
-        // turn around and move!
-        player_movement.target_velocity = 130.0 * direction.normalize();
+        steer.write(SteerPlayer { target: cursor.0 });
 
         unpause.set(Pause(false));
     }
@@ -164,15 +295,11 @@ fn handle_player_input(
 fn handle_player_input_touch(
     touches: Res<Touches>,
     mut unpause: ResMut<NextState<Pause>>,
-    mut query_player: Query<(&Transform, &mut Movement), With<Player>>,
-
+    mut steer: EventWriter<SteerPlayer>,
     q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    interactions: Query<&Interaction>,
 ) {
-    let Ok((player_transform, mut player_movement)) = query_player.single_mut() else {
-        return;
-    };
-
-    if touches.any_just_pressed() {
+    if touches.any_just_pressed() && !is_over_ui(&interactions) {
         let Some(pos) = touches.first_pressed_position() else {
             return;
         };
@@ -198,30 +325,32 @@ fn handle_player_input_touch(
             pos, world_position
         );
 
-        let player_pos = player_transform.translation.xy();
-        let target_pos = world_position;
+        steer.write(SteerPlayer {
+            target: world_position,
+        });
+
+        unpause.set(Pause(false));
+    }
+}
+
+/// Applies the recorded input in the fixed timestep, so it acts on the simulated position.
+fn steer_player(
+    mut events: EventReader<SteerPlayer>,
+    tuning: Res<Tuning>,
+    mut player: Single<(&Transform, &mut Movement), With<Player>>,
+) {
+    let (player_transform, player_movement) = &mut *player;
 
+    for event in events.read() {
         // direction the player wants to move to
-        let direction = target_pos - player_pos;
+        let direction = event.target - player_transform.translation.xy();
 
         // clicked on the player itself
         if direction.length_squared() < 0.01 {
-            return;
+            continue;
         }
 
         // turn around and move!
-        player_movement.target_velocity = 130.0 * direction.normalize();
-
-        unpause.set(Pause(false));
+        player_movement.target_velocity = tuning.player.speed * direction.normalize();
     }
 }
-
-fn camera_follow_player(
-    mut camera: Single<&mut Transform, With<MainCamera>>,
-    player: Single<&Transform, (With<Player>, Without<MainCamera>)>,
-) {
-    let target = player.translation.xy();
-
-    camera.translation.x = target.x;
-    camera.translation.y = target.y;
-}
@@ -1,49 +1,207 @@
+use crate::asset_tracking::LoadResource;
 use crate::game;
-use crate::game::enemy::{Awake, Enemy};
+use crate::game::audio::{PlaySound, Sound};
+use crate::game::cursor::WorldCursor;
+use crate::game::decoy::decoy_bundle;
+use crate::game::enemy::{Awake, Awaking, Enemy, fall_asleep};
+use crate::game::explosion::{
+    ChainSettings, DelayedExplosion, Placement, delayed_explosion_bundle,
+};
 use crate::game::hud::AddScore;
-use crate::game::movement::Movement;
-use crate::game::player::Player;
+use crate::game::inventory::Inventory;
+use crate::game::particles::{EmitParticles, ParticleEffect};
+use crate::game::player::{self, Player};
 use crate::game::rand::Rand;
 use crate::game::screens::Screen;
 use crate::game::squishy::Squishy;
-use avian2d::prelude::{Collider, Collisions, Sensor};
+use crate::game::status::{StatusEffects, StatusKind};
+use avian2d::prelude::{
+    AngularVelocity, Collider, Collisions, LinearVelocity, Sensor, TranslationInterpolation,
+};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
 use bevy::ecs::system::RunSystemOnce;
-use bevy::math::FloatPow;
 use bevy::prelude::*;
 use bevy::sprite::Anchor;
 use rand::Rng;
+use rand::distr::weighted::WeightedIndex;
+use serde::Deserialize;
 use std::time::Duration;
 
 pub fn plugin(app: &mut App) {
+    app.init_asset::<PowerupDefinitions>();
+    app.init_asset_loader::<PowerupDefinitionsLoader>();
+    app.load_resource_from_path::<PowerupDefinitions>("powerups.json");
+    app.hot_reload_resource::<PowerupDefinitions>();
+
     app.add_systems(
-        Update,
-        (
-            collect_powerup,
-            handle_delayed_explosions,
-            explosion_fade_out,
-        )
-            .chain()
-            .run_if(in_state(Screen::Gameplay)),
+        FixedUpdate,
+        collect_powerup.run_if(in_state(Screen::Gameplay)),
     );
 }
 
+/// A powerup lying around in the world, referencing its definition by index.
 #[derive(Copy, Clone, Component, Debug)]
-pub enum Powerup {
-    Speed,
-    Explosion,
-    Coin,
+pub struct Powerup(pub usize);
+
+/// What happens when a powerup is collected.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PowerupEffect {
+    /// Applies a timed status effect to the player.
+    Status {
+        status: StatusKind,
+        duration_secs: f32,
+        magnitude: f32,
+    },
+    /// Kills all enemies in a random radius after a delay.
+    Explosion {
+        delay_secs: f32,
+        radius: [f32; 2],
+        /// Lets killed awake enemies explode as well.
+        #[serde(default)]
+        chain: Option<ChainSettings>,
+        #[serde(default)]
+        placement: Placement,
+    },
+    /// Adds a random score, rounded down to a multiple of `step`.
+    Coin {
+        score: [u32; 2],
+        #[serde(default = "default_step")]
+        step: u32,
+    },
+    /// Puts all awake enemies within the radius back to sleep.
+    Freeze { radius: f32 },
+    /// Leaves a decoy at the players position that distracts awake enemies.
+    Decoy { duration_secs: f32 },
+    /// Moves the player towards the cursor, but at most `range` units.
+    Teleport { range: f32 },
 }
 
-pub fn powerup_bundle(assets: &game::Assets, powerup: Powerup) -> impl Bundle {
-    (
+fn default_step() -> u32 {
+    1
+}
+
+impl PowerupEffect {
+    /// Rejects values that would make applying the effect panic.
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            PowerupEffect::Explosion {
+                radius: [min, max], ..
+            } if min.is_nan() || max.is_nan() || min > max => {
+                Err(format!("invalid radius range {min}..={max}"))
+            }
+
+            PowerupEffect::Coin { step: 0, .. } => Err(String::from("step must not be zero")),
+
+            PowerupEffect::Coin {
+                score: [min, max],
+                step,
+            } if min / step > max / step => Err(format!("invalid score range {min}..={max}")),
+
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PowerupDefinition {
+    pub id: String,
+    pub sprite: Handle<Image>,
+    pub tint: Color,
+    /// Relative chance of this powerup being placed in the world.
+    pub weight: f32,
+    pub effect: PowerupEffect,
+}
+
+/// All powerups available in the game, loaded from `powerups.json`.
+#[derive(Clone, Debug, Resource, Asset, TypePath)]
+pub struct PowerupDefinitions {
+    pub powerups: Vec<PowerupDefinition>,
+}
+
+impl PowerupDefinitions {
+    pub fn get(&self, powerup: Powerup) -> Option<&PowerupDefinition> {
+        self.powerups.get(powerup.0)
+    }
+
+    /// Picks a random powerup respecting the configured weights.
+    pub fn choose(&self, rand: &mut impl Rng) -> Option<Powerup> {
+        let weights = WeightedIndex::new(self.powerups.iter().map(|p| p.weight)).ok()?;
+        Some(Powerup(rand.sample(weights)))
+    }
+}
+
+#[derive(Deserialize)]
+struct PowerupsFile {
+    powerups: Vec<PowerupEntry>,
+}
+
+#[derive(Deserialize)]
+struct PowerupEntry {
+    id: String,
+    sprite: String,
+    tint: String,
+    weight: f32,
+    effect: PowerupEffect,
+}
+
+#[derive(Default)]
+struct PowerupDefinitionsLoader;
+
+impl AssetLoader for PowerupDefinitionsLoader {
+    type Asset = PowerupDefinitions;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let file: PowerupsFile = serde_json::from_slice(&bytes)?;
+
+        let mut powerups = Vec::with_capacity(file.powerups.len());
+        for entry in file.powerups {
+            let tint = Srgba::hex(&entry.tint)
+                .map_err(|err| format!("invalid tint of powerup {:?}: {}", entry.id, err))?;
+
+            entry
+                .effect
+                .validate()
+                .map_err(|err| format!("invalid effect of powerup {:?}: {}", entry.id, err))?;
+
+            powerups.push(PowerupDefinition {
+                sprite: load_context.load(entry.sprite),
+                tint: tint.into(),
+                weight: entry.weight,
+                effect: entry.effect,
+                id: entry.id,
+            });
+        }
+
+        Ok(PowerupDefinitions { powerups })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["powerups.json"]
+    }
+}
+
+/// Returns `None` if the definition of the powerup does not exist (anymore).
+pub fn powerup_bundle(definitions: &PowerupDefinitions, powerup: Powerup) -> Option<impl Bundle> {
+    let definition = definitions.get(powerup)?;
+
+    Some((
         powerup,
+        Name::new(format!("Powerup {}", definition.id)),
         Sprite {
-            image: match powerup {
-                Powerup::Speed => assets.up_speed.clone(),
-                Powerup::Explosion => assets.up_explosion.clone(),
-                Powerup::Coin => assets.up_coin.clone(),
-            },
-            color: Color::oklch(0.868, 0.174, 90.43),
+            image: definition.sprite.clone(),
+            color: definition.tint,
             custom_size: Some(Vec2::splat(48.0)),
             anchor: Anchor::Center,
             ..default()
@@ -56,7 +214,9 @@ pub fn powerup_bundle(assets: &game::Assets, powerup: Powerup) -> impl Bundle {
         },
         Sensor,
         Collider::circle(24.0),
-    )
+        // pulled by the magnet in the fixed timestep
+        TranslationInterpolation,
+    ))
 }
 
 pub struct ApplyPowerup(pub Powerup);
@@ -65,26 +225,65 @@ impl Command for ApplyPowerup {
     fn apply(self, world: &mut World) {
         let Self(powerup) = self;
 
-        match powerup {
-            Powerup::Speed => {
-                _ = world.run_system_once(apply_powerup_speed);
+        let Some(definition) = world
+            .get_resource::<PowerupDefinitions>()
+            .and_then(|definitions| definitions.get(powerup))
+        else {
+            warn!("No definition found for powerup {:?}", powerup);
+            return;
+        };
+
+        match definition.effect.clone() {
+            PowerupEffect::Status {
+                status,
+                duration_secs,
+                magnitude,
+            } => {
+                _ = world.run_system_once_with(
+                    apply_powerup_status,
+                    (status, Duration::from_secs_f32(duration_secs), magnitude),
+                );
+            }
+            PowerupEffect::Explosion {
+                delay_secs,
+                radius,
+                chain,
+                placement,
+            } => {
+                _ = world.run_system_once_with(
+                    apply_powerup_explosion,
+                    (delay_secs, radius, chain, placement),
+                );
+            }
+            PowerupEffect::Coin { score, step } => {
+                _ = world.run_system_once_with(apply_powerup_coin, (score, step));
+            }
+            PowerupEffect::Freeze { radius } => {
+                _ = world.run_system_once_with(apply_powerup_freeze, radius);
             }
-            Powerup::Explosion => {
-                _ = world.run_system_once(apply_powerup_explosion);
+            PowerupEffect::Decoy { duration_secs } => {
+                _ = world.run_system_once_with(
+                    apply_powerup_decoy,
+                    Duration::from_secs_f32(duration_secs),
+                );
             }
-            Powerup::Coin => {
-                _ = world.run_system_once(apply_powerup_coin);
+            PowerupEffect::Teleport { range } => {
+                _ = world.run_system_once_with(apply_powerup_teleport, range);
             }
         }
     }
 }
 
-fn apply_powerup_speed(mut player: Single<&mut Movement, With<Player>>) {
-    info!("Double the players speed until the next turn.");
-    player.target_velocity *= 2.0;
+fn apply_powerup_status(
+    In((status, duration, magnitude)): In<(StatusKind, Duration, f32)>,
+    mut player: Single<&mut StatusEffects, With<Player>>,
+) {
+    info!("Apply status {:?} for {:?}", status, duration);
+    player.add(status, duration, magnitude);
 }
 
 fn apply_powerup_coin(
+    In(([min, max], step)): In<([u32; 2], u32)>,
     mut rand: ResMut<Rand>,
     mut player: Single<(&mut Player, &Transform)>,
     mut add_score: EventWriter<AddScore>,
@@ -92,129 +291,151 @@ fn apply_powerup_coin(
     let (player, player_transform) = &mut *player;
 
     // add bonus score
-    let score = player.add_score(rand.random_range(3..=6) * 10);
+    let step = step.max(1);
+    let score = player.add_score(rand.random_range(min / step..=max / step) * step);
     add_score.write(AddScore {
         score,
         position: player_transform.translation.xy(),
     });
 }
 
-fn apply_powerup_explosion(mut commands: Commands, player: Single<Entity, With<Player>>) {
-    let label = commands
-        .spawn((Text2d::new("Foobar"), Anchor::BottomLeft))
-        .id();
-
-    commands.entity(*player).insert(DelayedExplosion {
-        timer: Timer::from_seconds(2.0, TimerMode::Once),
-        label,
-    });
-}
-
-fn handle_delayed_explosions(
+fn apply_powerup_freeze(
+    In(radius): In<f32>,
     mut commands: Commands,
-    mut rand: ResMut<Rand>,
-    mut player: Single<(Entity, &mut DelayedExplosion, &mut Player, &Transform), Without<Text2d>>,
-    mut label: Query<(&mut Text2d, &mut Transform)>,
-    enemies: Query<(Entity, &Transform, Has<Awake>), (With<Enemy>, Without<Text2d>)>,
-    assets: Res<game::Assets>,
-    time: Res<Time>,
-    mut add_score: EventWriter<AddScore>,
+    time: Res<Time<Fixed>>,
+    player: Single<&Transform, With<Player>>,
+    mut enemies: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut LinearVelocity,
+            &mut AngularVelocity,
+        ),
+        (
+            With<Enemy>,
+            Or<(With<Awake>, With<Awaking>)>,
+            Without<Player>,
+        ),
+    >,
+    mut particles: EventWriter<EmitParticles>,
 ) {
-    let (player_entity, explosion, player, player_transform) = &mut *player;
-
-    if !explosion.timer.tick(time.delta()).just_finished() {
-        if let Ok((mut text, mut transform)) = label.get_mut(explosion.label) {
-            transform.translation.x = player_transform.translation.x + 24.0;
-            transform.translation.y = player_transform.translation.y + 24.0;
+    let player_pos = player.translation.xy();
 
-            text.0 = format!("boom in {:1.2}s", explosion.timer.remaining_secs());
+    for (enemy, mut transform, mut velocity, mut angular_velocity) in &mut enemies {
+        if transform.translation.xy().distance(player_pos) > radius {
+            continue;
         }
 
-        return;
+        velocity.0 = Vec2::ZERO;
+        angular_velocity.0 = 0.0;
+        transform.scale = Vec3::ONE;
+
+        fall_asleep(&mut commands, enemy, time.elapsed());
     }
 
-    // remove the label if it still exists
-    commands.entity(explosion.label).try_despawn();
+    particles.write(EmitParticles {
+        position: player_pos,
+        effect: ParticleEffect::Frost { radius },
+    });
+}
 
-    // remove the scheduled explosion from the player
-    commands.entity(*player_entity).remove::<DelayedExplosion>();
+fn apply_powerup_decoy(
+    In(lifetime): In<Duration>,
+    mut commands: Commands,
+    assets: Res<game::Assets>,
+    player: Single<&Transform, With<Player>>,
+) {
+    commands.spawn(decoy_bundle(&assets, player.translation.xy(), lifetime));
+}
 
-    let blast_radius = rand.random_range(200.0..300.0);
+fn apply_powerup_teleport(
+    In(range): In<f32>,
+    cursor: Res<WorldCursor>,
+    mut player: Single<(&mut Transform, &Sprite), With<Player>>,
+    mut particles: EventWriter<EmitParticles>,
+) {
+    let (transform, sprite) = &mut *player;
 
-    for (enemy, enemy_transform, enemy_is_awake) in enemies {
-        let distance = enemy_transform
-            .translation
-            .xy()
-            .distance(player_transform.translation.xy());
+    let start = transform.translation.xy();
 
-        if distance > blast_radius {
-            continue;
-        }
+    // never end up behind the outer rim
+    let target = (start + (cursor.0 - start).clamp_length_max(range))
+        .clamp_length_max(game::ARENA_RADIUS - player::RADIUS);
 
-        // kill enemy
-        commands.entity(enemy).despawn();
+    transform.translation = target.extend(transform.translation.z);
 
-        add_score.write(AddScore {
-            score: player.add_kill(enemy_is_awake),
-            position: enemy_transform.translation.xy(),
+    for position in [start, target] {
+        particles.write(EmitParticles {
+            position,
+            effect: ParticleEffect::PowerupPickup {
+                color: sprite.color,
+            },
         });
     }
-
-    // spawn an explosion circle
-    commands.spawn((
-        Name::new("Explosion"),
-        StateScoped(Screen::Gameplay),
-        Transform::from_translation(player_transform.translation.with_z(0.0)),
-        Explosion(Timer::from_seconds(0.25, TimerMode::Once)),
-        Sprite {
-            image: assets.circle.clone(),
-            anchor: Anchor::Center,
-            custom_size: Some(Vec2::splat(2.0 * blast_radius * 1.1)),
-            color: Color::srgba(1.0, 1.0, 1.0, 0.75),
-            ..default()
-        },
-    ));
-}
-
-#[derive(Component)]
-struct DelayedExplosion {
-    timer: Timer,
-    label: Entity,
 }
 
-#[derive(Component)]
-struct Explosion(Timer);
-
-fn explosion_fade_out(
+fn apply_powerup_explosion(
+    In((delay_secs, [min_radius, max_radius], chain, placement)): In<(
+        f32,
+        [f32; 2],
+        Option<ChainSettings>,
+        Placement,
+    )>,
     mut commands: Commands,
-    mut explosions: Query<(Entity, &mut Sprite, &mut Explosion)>,
-    time: ResMut<Time<Virtual>>,
+    mut rand: ResMut<Rand>,
+    assets: Res<game::Assets>,
+    player: Single<(Entity, &Transform), With<Player>>,
 ) {
-    for (entity, mut sprite, mut explosion) in &mut explosions {
-        if explosion.0.tick(time.delta()).just_finished() {
-            commands.entity(entity).despawn();
-            continue;
-        }
+    let (player, player_transform) = *player;
 
-        let alpha = explosion.0.fraction_remaining().squared();
-        sprite.color.set_alpha(alpha);
-    }
+    commands.spawn(delayed_explosion_bundle(
+        &assets,
+        DelayedExplosion {
+            timer: Timer::from_seconds(delay_secs, TimerMode::Once),
+            radius: rand.random_range(min_radius..=max_radius),
+            chain,
+            placement,
+            owner: player,
+        },
+        player_transform.translation.xy(),
+    ));
 }
 
 fn collect_powerup(
     mut commands: Commands,
     collisions: Collisions,
-    query_powerups: Query<(Entity, &Powerup)>,
-    player: Single<Entity, With<Player>>,
+    query_powerups: Query<(Entity, &Powerup, &Transform, &Sprite)>,
+    mut player: Single<(Entity, &mut Player)>,
+    mut inventory: ResMut<Inventory>,
+    mut particles: EventWriter<EmitParticles>,
+    mut sounds: EventWriter<PlaySound>,
 ) {
-    for (powerup_entity, powerup) in &query_powerups {
+    let (player_entity, player) = &mut *player;
+
+    for (powerup_entity, powerup, powerup_transform, powerup_sprite) in &query_powerups {
         for collider in collisions.entities_colliding_with(powerup_entity) {
-            if collider != *player {
+            if collider != *player_entity {
                 continue;
             }
 
-            // apply entity to player
-            commands.queue(ApplyPowerup(*powerup));
+            player.powerups_collected += 1;
+
+            particles.write(EmitParticles {
+                position: powerup_transform.translation.xy(),
+                effect: ParticleEffect::PowerupPickup {
+                    color: powerup_sprite.color,
+                },
+            });
+
+            sounds.write(PlaySound {
+                sound: Sound::Pickup,
+                position: powerup_transform.translation.xy(),
+            });
+
+            // keep it for later or apply it to the player right away
+            if !inventory.store(*powerup) {
+                commands.queue(ApplyPowerup(*powerup));
+            }
 
             // remove the powerup entity
             commands.entity(powerup_entity).despawn();
@@ -1,6 +1,8 @@
+use crate::game::mode::{self, GameMode, Today};
+use crate::game::screens::Screen;
 use bevy::app::App;
 use bevy::math::{FloatPow, Vec2, vec2};
-use bevy::prelude::Resource;
+use bevy::prelude::{IntoScheduleConfigs, OnEnter, Res, ResMut, Resource};
 use fastnoise_lite::FastNoiseLite;
 use rand::{Rng, RngCore, SeedableRng};
 
@@ -37,9 +39,32 @@ impl Rand {
     }
 }
 
+/// The seed the current run was generated from.
+#[derive(Resource, Copy, Clone, Debug)]
+pub struct Seed(pub u64);
+
 pub fn plugin(app: &mut App) {
     let r = rand::rngs::SmallRng::seed_from_u64(1);
     app.insert_resource(Rand(r));
+    app.insert_resource(Seed(1));
+
+    app.add_systems(OnEnter(Screen::Reset), next_seed.after(mode::apply_rules));
+}
+
+fn next_seed(
+    mut rand: ResMut<Rand>,
+    mut seed: ResMut<Seed>,
+    mode: Res<GameMode>,
+    today: Res<Today>,
+) {
+    // derive the seed of the next run from the current state, or use the
+    // seed of the day, and restart the random number generator with it.
+    seed.0 = match *mode {
+        GameMode::Daily => today.seed(),
+        _ => rand.next_u64(),
+    };
+
+    rand.0 = rand::rngs::SmallRng::seed_from_u64(seed.0);
 }
 
 pub struct Occupied {
@@ -79,13 +104,40 @@ impl Generate {
         }
     }
 
-    pub fn generate<Fn>(&mut self, mut random_point: Fn, count: usize, clearance: f32) -> Vec<Vec2>
+    /// Marks an already existing object, so new points keep their distance to it.
+    pub fn occupy(&mut self, position: Vec2, clearance: f32) {
+        self.occupied.push(Occupied {
+            position,
+            clearance,
+        });
+    }
+
+    pub fn generate<Fn>(&mut self, random_point: Fn, count: usize, clearance: f32) -> Vec<Vec2>
+    where
+        Fn: FnMut(f32) -> Vec2,
+    {
+        self.try_generate(random_point, count, clearance, usize::MAX)
+    }
+
+    /// Like [`Generate::generate`], but gives up after the given number of candidates
+    /// were drawn. Returns fewer points if there was not enough free space.
+    pub fn try_generate<Fn>(
+        &mut self,
+        mut random_point: Fn,
+        count: usize,
+        clearance: f32,
+        attempts: usize,
+    ) -> Vec<Vec2>
     where
         Fn: FnMut(f32) -> Vec2,
     {
         let mut positions = Vec::with_capacity(count);
 
-        while positions.len() < count {
+        for _ in 0..attempts {
+            if positions.len() >= count {
+                break;
+            }
+
             let offset = random_point(self.max_radius);
 
             if !(self.min_radius..self.max_radius).contains(&offset.length()) {
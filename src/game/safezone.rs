@@ -1,4 +1,5 @@
 use crate::game::EndGame;
+use crate::game::particles::{EmitParticles, ParticleEffect};
 use crate::game::player::Player;
 use crate::game::screens::Screen;
 use crate::{PausableSystems, game};
@@ -7,10 +8,16 @@ use bevy::prelude::*;
 use bevy::sprite::Anchor;
 
 pub fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        safezone_reached
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(PausableSystems),
+    );
+
     app.add_systems(
         Update,
-        (safezone_reached, safezone_sync_color)
-            .chain()
+        safezone_sync_color
             .run_if(in_state(Screen::Gameplay))
             .in_set(PausableSystems),
     );
@@ -55,10 +62,11 @@ fn safezone_sync_color(
 fn safezone_reached(
     mut commands: Commands,
     collisions: Collisions,
-    query_safezones: Query<Entity, With<Safezone>>,
+    query_safezones: Query<(Entity, &Transform), With<Safezone>>,
     mut query_player: Query<&mut Player>,
+    mut particles: EventWriter<EmitParticles>,
 ) {
-    for safezone_entity in &query_safezones {
+    for (safezone_entity, safezone_transform) in &query_safezones {
         for collider in collisions.entities_colliding_with(safezone_entity) {
             let Ok(mut player) = query_player.get_mut(collider) else {
                 continue;
@@ -67,6 +75,11 @@ fn safezone_reached(
             // give player an extra bonus for reaching the safezone
             player.safezone_reached = true;
 
+            particles.write(EmitParticles {
+                position: safezone_transform.translation.xy(),
+                effect: ParticleEffect::SafezoneArrival,
+            });
+
             commands.queue(EndGame { win: true });
         }
     }
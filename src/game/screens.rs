@@ -10,6 +10,7 @@ pub fn plugin(app: &mut App) {
 pub enum Screen {
     #[default]
     Loading,
+    Menu,
     Reset,
     Gameplay,
 }
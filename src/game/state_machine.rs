@@ -0,0 +1,110 @@
+//! A small state machine where each state is a component on the entity.
+//!
+//! An entity is in at most one state of a [`Machine`] at a time. Switching states
+//! with [`Transition::transition`] runs the exit hook of the old state, swaps the
+//! components, runs the enter hook of the new state and writes a [`Transitioned`] event.
+
+use bevy::ecs::error::ignore;
+use bevy::ecs::system::EntityCommand;
+use bevy::prelude::*;
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+/// Marker for a set of states that belong together, e.g. the behaviour of an enemy.
+pub trait Machine: Send + Sync + 'static {}
+
+/// A state of machine [`MachineState::Machine`], stored as a component while the entity is in it.
+pub trait MachineState: Component + Sized {
+    type Machine: Machine;
+
+    /// Runs after the state was inserted into the entity.
+    fn enter(_entity: &mut EntityWorldMut) {}
+
+    /// Runs before the state is removed from the entity.
+    fn exit(_entity: &mut EntityWorldMut) {}
+}
+
+pub trait AddMachine {
+    /// Registers the [`Transitioned`] event of the machine.
+    fn add_machine<M: Machine>(&mut self) -> &mut Self;
+}
+
+impl AddMachine for App {
+    fn add_machine<M: Machine>(&mut self) -> &mut Self {
+        self.add_event::<Transitioned<M>>();
+        self
+    }
+}
+
+/// Written after an entity switched to another state of machine `M`.
+#[derive(Event)]
+pub struct Transitioned<M: Machine> {
+    pub entity: Entity,
+    to: TypeId,
+    _machine: PhantomData<M>,
+}
+
+impl<M: Machine> Transitioned<M> {
+    /// Returns true if the entity switched into state `S`.
+    pub fn entered<S: MachineState<Machine = M>>(&self) -> bool {
+        self.to == TypeId::of::<S>()
+    }
+}
+
+/// The state of machine `M` the entity is currently in.
+#[derive(Component)]
+pub struct CurrentState<M: Machine> {
+    leave: fn(&mut EntityWorldMut),
+    _machine: PhantomData<M>,
+}
+
+impl<M: Machine> CurrentState<M> {
+    fn of<S: MachineState<Machine = M>>() -> Self {
+        Self {
+            leave: |entity| {
+                S::exit(entity);
+                entity.remove::<S>();
+            },
+            _machine: PhantomData,
+        }
+    }
+}
+
+/// Puts a newly spawned entity into its first state, without running the enter hook.
+pub fn initial_state<S: MachineState>(state: S) -> impl Bundle {
+    (state, CurrentState::<S::Machine>::of::<S>())
+}
+
+pub trait Transition {
+    /// Switches the entity into the given state. Does nothing if the entity
+    /// was despawned in the meantime.
+    fn transition<S: MachineState>(&mut self, state: S) -> &mut Self;
+}
+
+impl Transition for EntityCommands<'_> {
+    fn transition<S: MachineState>(&mut self, state: S) -> &mut Self {
+        self.queue_handled(TransitionTo(state), ignore)
+    }
+}
+
+struct TransitionTo<S>(S);
+
+impl<S: MachineState> EntityCommand for TransitionTo<S> {
+    fn apply(self, mut entity: EntityWorldMut) {
+        if let Some(current) = entity.take::<CurrentState<S::Machine>>() {
+            (current.leave)(&mut entity);
+        }
+
+        entity.insert((self.0, CurrentState::<S::Machine>::of::<S>()));
+        S::enter(&mut entity);
+
+        let id = entity.id();
+        entity.world_scope(|world| {
+            world.send_event(Transitioned::<S::Machine> {
+                entity: id,
+                to: TypeId::of::<S>(),
+                _machine: PhantomData,
+            });
+        });
+    }
+}
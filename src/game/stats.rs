@@ -0,0 +1,297 @@
+use crate::game::difficulty::DifficultyPreset;
+use crate::game::screens::Screen;
+use crate::game::storage;
+use crate::{AppSystems, Pause};
+use bevy::prelude::*;
+use bevy::ui::{Node, Val};
+use serde::{Deserialize, Serialize};
+
+/// Key of the stats in the local storage.
+const STORAGE_KEY: &str = "stats";
+
+/// The number of runs we keep in the history.
+const HISTORY_LEN: usize = 100;
+
+/// The number of runs to compare when calculating the trend.
+const TREND_WINDOW: usize = 10;
+
+pub fn plugin(app: &mut App) {
+    app.insert_resource(storage::load::<Stats>(STORAGE_KEY).unwrap_or_default());
+    app.init_state::<StatsOverlay>();
+
+    app.add_systems(OnEnter(StatsOverlay::Open), display_stats);
+    app.add_systems(OnEnter(Screen::Reset), close_stats);
+
+    app.add_systems(
+        Update,
+        toggle_stats
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::RecordInput),
+    );
+}
+
+#[derive(States, Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[states(scoped_entities)]
+enum StatsOverlay {
+    Open,
+    #[default]
+    Closed,
+}
+
+/// A single finished run.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Unix timestamp of the end of the run.
+    pub timestamp: u64,
+    pub seed: u64,
+    pub mode: String,
+    /// Runs recorded before the difficulty could be selected were played on normal.
+    #[serde(default = "default_difficulty")]
+    pub difficulty: String,
+    pub score: u32,
+    pub duration_secs: f32,
+    pub kills: u32,
+    pub win: bool,
+    pub powerups: u32,
+}
+
+/// Aggregates over all runs ever played, even those that
+/// already dropped out of the history.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Lifetime {
+    pub runs: u32,
+    pub wins: u32,
+    pub kills: u64,
+    pub powerups: u64,
+    pub score: u64,
+    pub play_time_secs: f64,
+    pub best_score: u32,
+    pub best_kills: u32,
+    pub longest_run_secs: f32,
+}
+
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub lifetime: Lifetime,
+    /// The most recent runs, oldest first.
+    pub runs: Vec<RunRecord>,
+}
+
+impl Stats {
+    fn record(&mut self, run: RunRecord) {
+        let lifetime = &mut self.lifetime;
+        lifetime.runs += 1;
+        lifetime.wins += run.win as u32;
+        lifetime.kills += run.kills as u64;
+        lifetime.powerups += run.powerups as u64;
+        lifetime.score += run.score as u64;
+        lifetime.play_time_secs += run.duration_secs as f64;
+        lifetime.best_score = lifetime.best_score.max(run.score);
+        lifetime.best_kills = lifetime.best_kills.max(run.kills);
+        lifetime.longest_run_secs = lifetime.longest_run_secs.max(run.duration_secs);
+
+        self.runs.push(run);
+
+        if self.runs.len() > HISTORY_LEN {
+            let excess = self.runs.len() - HISTORY_LEN;
+            self.runs.drain(..excess);
+        }
+    }
+
+    /// Average score of the last few runs compared to the runs before.
+    /// Returns `None` if there are not enough runs yet.
+    pub fn score_trend(&self) -> Option<f32> {
+        let scores: Vec<_> = self.runs.iter().rev().map(|r| r.score as f32).collect();
+        if scores.len() < 2 * TREND_WINDOW {
+            return None;
+        }
+
+        let recent = scores[..TREND_WINDOW].iter().sum::<f32>();
+        let previous = scores[TREND_WINDOW..2 * TREND_WINDOW].iter().sum::<f32>();
+        Some((recent - previous) / TREND_WINDOW as f32)
+    }
+}
+
+fn default_difficulty() -> String {
+    DifficultyPreset::Normal.id(false)
+}
+
+/// Records a finished run and persists the stats.
+pub struct RecordRun(pub RunRecord);
+
+impl Command for RecordRun {
+    fn apply(self, world: &mut World) {
+        let mut stats = world.resource_mut::<Stats>();
+        stats.record(self.0);
+
+        storage::save(STORAGE_KEY, &*stats);
+    }
+}
+
+fn toggle_stats(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<StatsOverlay>>,
+    mut next_state: ResMut<NextState<StatsOverlay>>,
+    mut pause: ResMut<NextState<Pause>>,
+    mut time: ResMut<Time<Virtual>>,
+    // resume the game when closing the overlay
+    mut resume: Local<bool>,
+) {
+    if !keys.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    match *state.get() {
+        StatsOverlay::Closed => {
+            *resume = !time.is_paused();
+
+            // pause the game while looking at the stats
+            pause.set(Pause(true));
+            time.pause();
+
+            next_state.set(StatsOverlay::Open);
+        }
+
+        StatsOverlay::Open => {
+            if *resume {
+                pause.set(Pause(false));
+                time.unpause();
+            }
+
+            next_state.set(StatsOverlay::Closed);
+        }
+    }
+}
+
+fn close_stats(mut next_state: ResMut<NextState<StatsOverlay>>) {
+    next_state.set(StatsOverlay::Closed);
+}
+
+fn display_stats(mut commands: Commands, stats: Res<Stats>) {
+    let lifetime = &stats.lifetime;
+
+    let win_rate = if lifetime.runs > 0 {
+        100.0 * lifetime.wins as f32 / lifetime.runs as f32
+    } else {
+        0.0
+    };
+
+    let trend = match stats.score_trend() {
+        Some(delta) if delta >= 0.0 => format!("+{:.1}", delta),
+        Some(delta) => format!("{:.1}", delta),
+        None => String::from("-"),
+    };
+
+    let rows = [
+        ("Runs", lifetime.runs.to_string()),
+        ("Wins", format!("{} ({:.0}%)", lifetime.wins, win_rate)),
+        ("Kills", lifetime.kills.to_string()),
+        ("Powerups", lifetime.powerups.to_string()),
+        (
+            "Play time",
+            format!("{:.0}min", lifetime.play_time_secs / 60.0),
+        ),
+        ("Best score", lifetime.best_score.to_string()),
+        ("Most kills", lifetime.best_kills.to_string()),
+        ("Longest run", format!("{:.0}s", lifetime.longest_run_secs)),
+        ("Score trend", trend),
+    ];
+
+    // scores of the most recent runs, oldest first
+    let recent: Vec<_> = stats.runs.iter().rev().take(20).rev().collect();
+    let max_score = recent.iter().map(|r| r.score).max().unwrap_or(1).max(1);
+
+    commands
+        .spawn((
+            StateScoped(StatsOverlay::Open),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((Node {
+                    width: Val::Percent(100.0),
+                    max_width: Val::Px(320.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Start,
+                    align_self: AlignSelf::Center,
+                    margin: UiRect::px(32.0, 32.0, 32.0, 0.0),
+                    ..default()
+                },))
+                .with_children(|parent| {
+                    parent.spawn((
+                        // the title
+                        Text::new("Stats"),
+                        Node {
+                            margin: UiRect::bottom(Val::Px(16.0)),
+                            ..Default::default()
+                        },
+                    ));
+
+                    for (label, value) in rows {
+                        parent
+                            .spawn(Node {
+                                width: Val::Percent(100.0),
+                                flex_direction: FlexDirection::Row,
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(label),
+                                    Node {
+                                        flex_grow: 1.0,
+                                        ..default()
+                                    },
+                                ));
+
+                                parent.spawn((Text::new(value),));
+                            });
+                    }
+
+                    parent.spawn((
+                        Text::new("Recent runs"),
+                        Node {
+                            margin: UiRect::vertical(Val::Px(16.0)),
+                            ..Default::default()
+                        },
+                    ));
+
+                    // a small bar chart of the most recent scores
+                    parent
+                        .spawn(Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Px(64.0),
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::End,
+                            column_gap: Val::Px(2.0),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for run in recent {
+                                let color = if run.win {
+                                    crate::game::safezone::COLOR
+                                } else {
+                                    Color::srgb(0.6, 0.6, 0.6)
+                                };
+
+                                parent.spawn((
+                                    Node {
+                                        flex_grow: 1.0,
+                                        height: Val::Percent(
+                                            100.0 * run.score as f32 / max_score as f32,
+                                        ),
+                                        ..default()
+                                    },
+                                    BackgroundColor(color),
+                                ));
+                            }
+                        });
+                });
+        });
+}
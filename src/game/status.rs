@@ -0,0 +1,225 @@
+use crate::game::movement::Movement;
+use crate::game::player::Player;
+use crate::game::powerup::Powerup;
+use crate::game::screens::Screen;
+use crate::{AppSystems, PausableSystems};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Speed at which the magnet pulls powerups towards the player.
+const MAGNET_PULL_SPEED: f32 = 300.0;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (
+            tick_status_effects,
+            apply_speed_effects,
+            apply_magnet_effect,
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+
+    app.add_systems(
+        Update,
+        blink_while_invulnerable
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusKind {
+    /// Multiplies the movement speed by the magnitude.
+    SpeedBoost,
+    /// Awake enemies can not hurt the player.
+    Invulnerable,
+    /// Multiplies the movement speed by the magnitude, which is below one.
+    Slow,
+    /// Pulls in powerups within a radius given by the magnitude.
+    Magnet,
+    /// Survives a single hit of an awake enemy.
+    Shield,
+}
+
+/// What happens when an effect is added while one of the same kind is still active.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Stacking {
+    /// Restart the timer with the longer of both durations, keep the stronger magnitude.
+    Refresh,
+    /// Add the new duration to the remaining one.
+    Extend,
+}
+
+impl StatusKind {
+    pub const ALL: [StatusKind; 5] = [
+        StatusKind::SpeedBoost,
+        StatusKind::Invulnerable,
+        StatusKind::Slow,
+        StatusKind::Magnet,
+        StatusKind::Shield,
+    ];
+
+    pub fn stacking(self) -> Stacking {
+        match self {
+            StatusKind::SpeedBoost => Stacking::Refresh,
+            StatusKind::Invulnerable => Stacking::Extend,
+            StatusKind::Slow => Stacking::Refresh,
+            StatusKind::Magnet => Stacking::Extend,
+            StatusKind::Shield => Stacking::Refresh,
+        }
+    }
+
+    /// Symbol shown in the HUD while the effect is active.
+    pub fn glyph(self) -> &'static str {
+        match self {
+            StatusKind::SpeedBoost => ">>",
+            StatusKind::Invulnerable => "*",
+            StatusKind::Slow => "<<",
+            StatusKind::Magnet => "U",
+            StatusKind::Shield => "#",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            StatusKind::SpeedBoost => Color::oklch(0.868, 0.174, 90.43),
+            StatusKind::Invulnerable => Color::oklch(0.8, 0.1, 230.0),
+            StatusKind::Slow => Color::oklch(0.6, 0.15, 300.0),
+            StatusKind::Magnet => Color::oklch(0.7, 0.2, 25.0),
+            StatusKind::Shield => Color::oklch(0.75, 0.15, 200.0),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub magnitude: f32,
+    pub timer: Timer,
+}
+
+/// Timed effects currently active on an entity.
+#[derive(Component, Clone, Debug, Default)]
+pub struct StatusEffects(Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn add(&mut self, kind: StatusKind, duration: Duration, magnitude: f32) {
+        let Some(existing) = self.0.iter_mut().find(|effect| effect.kind == kind) else {
+            self.0.push(StatusEffect {
+                kind,
+                magnitude,
+                timer: Timer::new(duration, TimerMode::Once),
+            });
+
+            return;
+        };
+
+        let remaining = existing.timer.remaining();
+
+        let duration = match kind.stacking() {
+            Stacking::Refresh => {
+                // keep whichever effect is stronger, slow effects are stronger when lower.
+                existing.magnitude = match kind {
+                    StatusKind::Slow => existing.magnitude.min(magnitude),
+                    _ => existing.magnitude.max(magnitude),
+                };
+
+                remaining.max(duration)
+            }
+
+            Stacking::Extend => remaining + duration,
+        };
+
+        existing.timer = Timer::new(duration, TimerMode::Once);
+    }
+
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.0.iter().find(|effect| effect.kind == kind)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    /// Removes an effect before it runs out, e.g. when it was used up.
+    pub fn remove(&mut self, kind: StatusKind) {
+        self.0.retain(|effect| effect.kind != kind);
+    }
+
+    fn speed_multiplier(&self) -> f32 {
+        self.0
+            .iter()
+            .filter(|effect| matches!(effect.kind, StatusKind::SpeedBoost | StatusKind::Slow))
+            .map(|effect| effect.magnitude)
+            .product()
+    }
+}
+
+fn tick_status_effects(time: Res<Time<Fixed>>, mut entities: Query<&mut StatusEffects>) {
+    for mut effects in &mut entities {
+        for effect in &mut effects.0 {
+            effect.timer.tick(time.delta());
+        }
+
+        if effects.0.iter().any(|effect| effect.timer.finished()) {
+            effects.0.retain(|effect| !effect.timer.finished());
+        }
+    }
+}
+
+fn apply_speed_effects(mut entities: Query<(&StatusEffects, &mut Movement)>) {
+    for (effects, mut movement) in &mut entities {
+        let multiplier = effects.speed_multiplier();
+        if movement.speed_multiplier != multiplier {
+            movement.speed_multiplier = multiplier;
+        }
+    }
+}
+
+fn apply_magnet_effect(
+    time: Res<Time<Fixed>>,
+    player: Single<(&Transform, &StatusEffects), With<Player>>,
+    mut powerups: Query<&mut Transform, (With<Powerup>, Without<Player>)>,
+) {
+    let (player_transform, effects) = *player;
+
+    let Some(magnet) = effects.get(StatusKind::Magnet) else {
+        return;
+    };
+
+    let player_pos = player_transform.translation.xy();
+
+    for mut transform in &mut powerups {
+        let offset = player_pos - transform.translation.xy();
+        let distance = offset.length();
+        if distance > magnet.magnitude || distance < 1.0 {
+            continue;
+        }
+
+        let step = (MAGNET_PULL_SPEED * time.delta_secs()).min(distance);
+        transform.translation += (offset / distance * step).extend(0.0);
+    }
+}
+
+fn blink_while_invulnerable(
+    time: Res<Time<Virtual>>,
+    mut entities: Query<(&StatusEffects, &mut Sprite), With<Player>>,
+) {
+    for (effects, mut sprite) in &mut entities {
+        let alpha = match effects.has(StatusKind::Invulnerable) {
+            true => 0.4 + 0.6 * (time.elapsed_secs() * 20.0).sin().abs(),
+            false => 1.0,
+        };
+
+        if sprite.color.alpha() != alpha {
+            sprite.color.set_alpha(alpha);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Tiny key/value persistence. Values are stored in `localStorage` on the web
+//! and as files in the users data directory on native builds.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::warn;
+
+/// Loads and deserializes the value stored under the given key.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let raw = read(key)?;
+
+    match serde_json::from_str(&raw) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("Failed to parse stored value {:?}: {:?}", key, err);
+            None
+        }
+    }
+}
+
+/// Serializes and stores the value under the given key.
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    match serde_json::to_string(value) {
+        Ok(raw) => write(key, &raw),
+        Err(err) => warn!("Failed to serialize value {:?}: {:?}", key, err),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(key: &str) -> Option<String> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    storage.get_item(&storage_key(key)).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(key: &str, value: &str) {
+    let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) else {
+        warn!("localStorage is not available, can not store {:?}", key);
+        return;
+    };
+
+    if let Err(err) = storage.set_item(&storage_key(key), value) {
+        warn!("Failed to write {:?} to localStorage: {:?}", key, err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage_key(key: &str) -> String {
+    format!("chainscape.{}", key)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(key: &str) -> Option<String> {
+    std::fs::read_to_string(storage_path(key)).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(key: &str, value: &str) {
+    let path = storage_path(key);
+
+    if let Some(parent) = path.parent()
+        && let Err(err) = std::fs::create_dir_all(parent)
+    {
+        warn!("Failed to create directory {:?}: {:?}", parent, err);
+        return;
+    }
+
+    if let Err(err) = std::fs::write(&path, value) {
+        warn!("Failed to write {:?}: {:?}", path, err);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn storage_path(key: &str) -> std::path::PathBuf {
+    use std::path::PathBuf;
+
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    base.join("chainscape").join(format!("{}.json", key))
+}
@@ -0,0 +1,156 @@
+use crate::asset_tracking::LoadResource;
+use crate::game::difficulty::{Difficulty, DifficultyPreset, DifficultyTuning};
+use crate::game::enemy::Archetype;
+use crate::game::movement::Movement;
+use crate::game::player::Player;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use rand::Rng;
+use rand::distr::weighted::WeightedIndex;
+use serde::Deserialize;
+
+pub fn plugin(app: &mut App) {
+    app.init_asset::<Tuning>();
+    app.init_asset_loader::<TuningLoader>();
+    app.load_resource_from_path::<Tuning>("tuning.json");
+    app.hot_reload_resource::<Tuning>();
+
+    app.add_systems(
+        Update,
+        apply_tuning.run_if(resource_exists_and_changed::<Tuning>),
+    );
+}
+
+/// Gameplay constants, loaded from `tuning.json`.
+#[derive(Clone, Debug, Resource, Asset, TypePath, Deserialize)]
+pub struct Tuning {
+    pub player: PlayerTuning,
+    pub score: ScoreTuning,
+    pub flocking: FlockingTuning,
+    pub difficulty: DifficultyPresets,
+}
+
+impl Tuning {
+    pub fn difficulty(&self, preset: DifficultyPreset) -> &DifficultyTuning {
+        match preset {
+            DifficultyPreset::Easy => &self.difficulty.easy,
+            DifficultyPreset::Normal => &self.difficulty.normal,
+            DifficultyPreset::Hard => &self.difficulty.hard,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PlayerTuning {
+    /// Velocity of the player when moving towards the cursor.
+    pub speed: f32,
+    /// How fast the player turns towards the direction it is moving to.
+    pub angular_velocity: f32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct ScoreTuning {
+    pub kill_sleeping: u32,
+    pub kill_awake: u32,
+    /// Awarded for reaching a safezone.
+    pub safezone_bonus: u32,
+    /// Awarded in time attack for every second a safezone was reached below par.
+    pub time_attack_per_second: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct FlockingTuning {
+    /// Without flocking, awake enemies only keep their distance to each other.
+    pub enabled: bool,
+    /// Awake enemies closer than this push each other away.
+    pub separation_radius: f32,
+    /// Awake enemies of the same archetype within this radius form a flock.
+    pub neighbor_radius: f32,
+    pub archetypes: ArchetypeWeights,
+}
+
+impl FlockingTuning {
+    pub fn archetype(&self, archetype: Archetype) -> &ArchetypeTuning {
+        match archetype {
+            Archetype::Straggler => &self.archetypes.straggler,
+            Archetype::Pack => &self.archetypes.pack,
+            Archetype::Swarm => &self.archetypes.swarm,
+        }
+    }
+
+    /// Picks a random archetype for a new enemy, respecting the spawn weights.
+    pub fn choose(&self, rand: &mut impl Rng) -> Archetype {
+        let weights = Archetype::ALL.map(|archetype| self.archetype(archetype).spawn_weight);
+
+        match WeightedIndex::new(weights) {
+            Ok(weights) => Archetype::ALL[rand.sample(weights)],
+            Err(_) => Archetype::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ArchetypeWeights {
+    pub straggler: ArchetypeTuning,
+    pub pack: ArchetypeTuning,
+    pub swarm: ArchetypeTuning,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ArchetypeTuning {
+    /// Relative chance of an enemy having this archetype.
+    pub spawn_weight: f32,
+    pub separation: f32,
+    /// Steering towards the heading of the flock.
+    pub alignment: f32,
+    /// Steering towards the center of the flock.
+    pub cohesion: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DifficultyPresets {
+    pub easy: DifficultyTuning,
+    pub normal: DifficultyTuning,
+    pub hard: DifficultyTuning,
+}
+
+#[derive(Default)]
+struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    type Asset = Tuning;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.json"]
+    }
+}
+
+/// Pushes the tuning into the running game, so changes apply while playing.
+fn apply_tuning(
+    tuning: Res<Tuning>,
+    difficulty: Option<ResMut<Difficulty>>,
+    mut players: Query<(&mut Player, &mut Movement)>,
+) {
+    if let Some(mut difficulty) = difficulty {
+        difficulty.tuning = tuning.difficulty(difficulty.preset).clone();
+    }
+
+    for (mut player, mut movement) in &mut players {
+        player.points = tuning.score;
+        movement.angular_velocity = tuning.player.angular_velocity;
+    }
+}
@@ -0,0 +1,167 @@
+use crate::game::difficulty::Difficulty;
+use crate::game::enemy::{Awake, Enemy, enemy_bundle};
+use crate::game::lod::DormantEnemies;
+use crate::game::mode::ModeRules;
+use crate::game::player::Player;
+use crate::game::powerup::Powerup;
+use crate::game::rand::{Generate, Rand};
+use crate::game::safezone::Safezone;
+use crate::game::screens::Screen;
+use crate::game::state_machine::Transition;
+use crate::game::tuning::Tuning;
+use crate::{AppSystems, PausableSystems, game};
+use bevy::prelude::*;
+use rand::Rng;
+use std::time::Duration;
+use tracing::info;
+
+const FIRST_WAVE_DELAY: Duration = Duration::from_secs(20);
+
+/// Enemies appear at least this far outside of the visible area.
+const SPAWN_MARGIN: f32 = 64.0;
+
+/// Width of the ring around the visible area in which enemies appear.
+const SPAWN_RING_WIDTH: f32 = 512.0;
+
+/// Minimum distance of a new enemy to any other object.
+const CLEARANCE: f32 = 32.0;
+
+/// Upper bound of the number of enemies in a single wave.
+const MAX_WAVE_SIZE: usize = 120;
+
+/// Number of candidate positions drawn per enemy before a crowded ring is given up on.
+const SPAWN_ATTEMPTS_PER_ENEMY: usize = 16;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Gameplay), start_waves);
+    app.add_systems(OnExit(Screen::Gameplay), stop_waves);
+
+    app.add_systems(
+        FixedUpdate,
+        spawn_waves
+            .run_if(in_state(Screen::Gameplay).and(resource_exists::<WaveDirector>))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// Spawns new enemies in waves of growing size, only exists if the game mode asks for it.
+#[derive(Resource)]
+pub struct WaveDirector {
+    /// The number of the last wave, zero before the first one arrived.
+    pub wave: u32,
+    /// Time until the next wave arrives.
+    pub next: Timer,
+}
+
+impl WaveDirector {
+    /// Number of sleeping and awake enemies in the given wave.
+    fn composition(wave: u32) -> (usize, usize) {
+        let total = (24 + 12 * wave as usize).min(MAX_WAVE_SIZE);
+
+        // later waves bring more enemies that hunt the player right away
+        let awake_fraction = (0.1 + 0.05 * wave as f32).min(0.6);
+        let awake = (total as f32 * awake_fraction).round() as usize;
+
+        (total - awake, awake)
+    }
+
+    /// Time between the given wave and the next one.
+    fn interval(wave: u32) -> Duration {
+        Duration::from_secs_f32((20.0 - wave as f32).max(8.0))
+    }
+}
+
+fn start_waves(mut commands: Commands, rules: Res<ModeRules>) {
+    if !rules.waves {
+        return;
+    }
+
+    commands.insert_resource(WaveDirector {
+        wave: 0,
+        next: Timer::new(FIRST_WAVE_DELAY, TimerMode::Once),
+    });
+}
+
+fn stop_waves(mut commands: Commands) {
+    commands.remove_resource::<WaveDirector>();
+}
+
+fn spawn_waves(
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+    mut director: ResMut<WaveDirector>,
+    mut rand: ResMut<Rand>,
+    assets: Res<game::Assets>,
+    difficulty: Res<Difficulty>,
+    tuning: Res<Tuning>,
+    player: Single<&Transform, With<Player>>,
+    obstacles: Query<&Transform, Or<(With<Enemy>, With<Powerup>, With<Safezone>)>>,
+    dormant: Res<DormantEnemies>,
+) {
+    if !director.next.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    director.wave += 1;
+
+    let wave = director.wave;
+    director.next = Timer::new(WaveDirector::interval(wave), TimerMode::Once);
+
+    // spawn just outside of what the player can see
+    let center = player.translation.xy();
+    let min_radius = game::VIEW_RADIUS + SPAWN_MARGIN;
+    let mut generator = Generate::new(min_radius + SPAWN_RING_WIDTH, min_radius, center);
+
+    for transform in &obstacles {
+        let position = transform.translation.xy();
+        if position.distance(center) < generator.max_radius + CLEARANCE {
+            generator.occupy(position, CLEARANCE);
+        }
+    }
+
+    for enemy in dormant.within(center, generator.max_radius + CLEARANCE) {
+        generator.occupy(enemy.position, CLEARANCE);
+    }
+
+    let (sleeping, awake) = WaveDirector::composition(wave);
+    let count = sleeping + awake;
+
+    let positions: Vec<_> = generator
+        .try_generate(
+            |radius| rand.vec2() * radius,
+            count,
+            CLEARANCE,
+            count * SPAWN_ATTEMPTS_PER_ENEMY,
+        )
+        .into_iter()
+        // keep the enemies within the arena
+        .filter(|pos| pos.length() < game::ARENA_RADIUS)
+        .collect();
+
+    info!(
+        "Spawning wave {} with {} of {} enemies, {} awake",
+        wave,
+        positions.len(),
+        count,
+        awake.min(positions.len())
+    );
+
+    for (idx, pos) in positions.into_iter().enumerate() {
+        let mut enemy = commands.spawn((
+            Name::new("Enemy"),
+            StateScoped(Screen::Gameplay),
+            enemy_bundle(
+                &assets,
+                tuning.flocking.choose(rand.as_mut()),
+                rand.random_range(difficulty.speed()),
+                Duration::ZERO,
+            ),
+            Transform::from_translation(pos.extend(1.0)),
+        ));
+
+        if idx < awake {
+            enemy.transition(Awake::new(rand.as_mut(), time.elapsed(), center));
+        }
+    }
+}
@@ -3,7 +3,11 @@
 // Disable console on Windows for non-dev builds.
 #![cfg_attr(not(feature = "dev"), windows_subsystem = "windows")]
 
+use crate::game::camera::{CameraController, MIN_VIEW_HEIGHT, MIN_VIEW_WIDTH};
 use crate::game::cursor::MainCamera;
+use avian2d::interpolation::PhysicsInterpolationPlugin;
+use bevy::audio::{AudioPlugin, SpatialScale};
+use bevy::ecs::schedule::{ExecutorKind, ScheduleLabel};
 use bevy::render::camera;
 use bevy::{asset::AssetMetaCheck, prelude::*};
 
@@ -28,6 +32,10 @@ impl Plugin for AppPlugin {
                     meta_check: AssetMetaCheck::Never,
                     ..default()
                 })
+                .set(AudioPlugin {
+                    default_spatial_scale: SpatialScale::new_2d(1.0 / game::audio::SPATIAL_SCALE),
+                    ..default()
+                })
                 .set(WindowPlugin {
                     primary_window: Window {
                         title: "Chainscape".to_string(),
@@ -39,8 +47,15 @@ impl Plugin for AppPlugin {
                 }),
         );
 
+        // Gameplay runs on a fixed timestep, rendering interpolates between the steps.
+        app.insert_resource(Time::<Fixed>::from_hz(SIMULATION_HZ));
+        app.edit_schedule(FixedUpdate, |schedule| {
+            // a fixed order of the systems keeps the simulation deterministic
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        });
+
         app.add_plugins((
-            avian2d::PhysicsPlugins::default(),
+            avian2d::PhysicsPlugins::default().set(PhysicsInterpolationPlugin::interpolate_all()),
             #[cfg(debug_assertions)]
             avian2d::debug_render::PhysicsDebugPlugin::default(),
         ));
@@ -49,26 +64,32 @@ impl Plugin for AppPlugin {
         app.add_plugins((asset_tracking::plugin, screens::plugin, game::plugin));
 
         // Order new `AppSystems` variants by adding them here:
-        app.configure_sets(
-            Update,
-            (
-                AppSystems::TickTimers,
-                AppSystems::RecordInput,
-                AppSystems::Update,
-            )
-                .chain(),
-        );
+        for schedule in [Update.intern(), FixedUpdate.intern()] {
+            app.configure_sets(
+                schedule,
+                (
+                    AppSystems::TickTimers,
+                    AppSystems::RecordInput,
+                    AppSystems::Update,
+                )
+                    .chain(),
+            );
+        }
 
         // Set up the `Pause` state.
         app.init_state::<Pause>();
         app.configure_sets(Update, PausableSystems.run_if(in_state(Pause(false))));
+        app.configure_sets(FixedUpdate, PausableSystems.run_if(in_state(Pause(false))));
 
         // Spawn the main camera.
         app.add_systems(Startup, spawn_camera);
     }
 }
 
-/// High-level groupings of systems for the app in the `Update` schedule.
+/// Rate of the fixed timestep all gameplay logic runs at.
+const SIMULATION_HZ: f64 = 64.0;
+
+/// High-level groupings of systems for the app in the `Update` and `FixedUpdate` schedules.
 /// When adding a new variant, make sure to order it in the `configure_sets`
 /// call above.
 #[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
@@ -94,14 +115,16 @@ fn spawn_camera(mut commands: Commands) {
     let mut projection = OrthographicProjection::default_2d();
 
     projection.scaling_mode = camera::ScalingMode::AutoMin {
-        min_height: 768.0,
-        min_width: 512.0,
+        min_height: MIN_VIEW_HEIGHT,
+        min_width: MIN_VIEW_WIDTH,
     };
 
     commands.spawn((
         Name::new("Camera"),
         Camera2d,
         MainCamera,
+        CameraController::default(),
+        SpatialListener::new(game::audio::SPATIAL_SCALE),
         Projection::Orthographic(projection),
         Msaa::Off,
     ));
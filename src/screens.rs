@@ -1,7 +1,8 @@
 use bevy::app::App;
 
 mod loading;
+mod menu;
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins((loading::plugin,));
+    app.add_plugins((loading::plugin, menu::plugin));
 }
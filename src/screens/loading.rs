@@ -10,7 +10,7 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(
         Update,
-        enter_gameplay_screen.run_if(in_state(Screen::Loading).and(all_assets_loaded)),
+        enter_menu_screen.run_if(in_state(Screen::Loading).and(all_assets_loaded)),
     );
 }
 
@@ -18,8 +18,8 @@ fn spawn_loading_screen(mut commands: Commands) {
     commands.spawn((StateScoped(Screen::Loading),));
 }
 
-fn enter_gameplay_screen(mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Reset);
+fn enter_menu_screen(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Menu);
 }
 
 fn all_assets_loaded(resource_handles: Res<ResourceHandles>) -> bool {
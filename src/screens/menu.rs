@@ -0,0 +1,222 @@
+//! The main menu in which the player selects the game mode.
+
+use bevy::prelude::*;
+
+use crate::game::difficulty::DifficultySelection;
+use crate::game::mode::GameMode;
+use crate::game::screens::Screen;
+
+const SELECT_KEYS: [[KeyCode; 2]; 4] = [
+    [KeyCode::Digit1, KeyCode::Numpad1],
+    [KeyCode::Digit2, KeyCode::Numpad2],
+    [KeyCode::Digit3, KeyCode::Numpad3],
+    [KeyCode::Digit4, KeyCode::Numpad4],
+];
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Menu), spawn_menu);
+
+    app.add_systems(
+        Update,
+        (
+            select_with_keys,
+            select_with_buttons,
+            difficulty_with_keys,
+            difficulty_with_buttons,
+            update_difficulty_labels,
+        )
+            .chain()
+            .run_if(in_state(Screen::Menu)),
+    );
+
+    app.add_systems(Update, return_to_menu.run_if(in_state(Screen::Gameplay)));
+}
+
+#[derive(Component)]
+struct ModeButton(GameMode);
+
+#[derive(Component, Copy, Clone, Eq, PartialEq)]
+enum DifficultyButton {
+    Preset,
+    Dynamic,
+}
+
+impl DifficultyButton {
+    fn label(self, selection: &DifficultySelection) -> String {
+        match self {
+            DifficultyButton::Preset => format!("difficulty: {} [D]", selection.preset.label()),
+
+            DifficultyButton::Dynamic => match selection.dynamic {
+                true => "dynamic: on [A]".to_string(),
+                false => "dynamic: off [A]".to_string(),
+            },
+        }
+    }
+}
+
+fn spawn_menu(
+    mut commands: Commands,
+    selected: Res<GameMode>,
+    difficulty: Res<DifficultySelection>,
+) {
+    commands
+        .spawn((
+            Name::new("Menu"),
+            StateScoped(Screen::Menu),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Chainscape"),
+                TextFont::from_font_size(48.0),
+                Node {
+                    margin: UiRect::bottom(Val::Px(24.0)),
+                    ..default()
+                },
+            ));
+
+            for (index, mode) in GameMode::ALL.into_iter().enumerate() {
+                let border = match mode == *selected {
+                    true => Color::WHITE,
+                    false => Color::srgba(1.0, 1.0, 1.0, 0.25),
+                };
+
+                parent
+                    .spawn((
+                        Button,
+                        ModeButton(mode),
+                        Node {
+                            width: Val::Px(320.0),
+                            padding: UiRect::all(Val::Px(12.0)),
+                            border: UiRect::all(Val::Px(2.0)),
+                            flex_direction: FlexDirection::Column,
+                            ..default()
+                        },
+                        BorderColor(border),
+                        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(Text::new(format!("{}. {}", index + 1, mode.label())));
+
+                        parent.spawn((
+                            Text::new(mode.description()),
+                            TextFont::from_font_size(14.0),
+                            TextColor(Color::srgba(1.0, 1.0, 1.0, 0.6)),
+                        ));
+                    });
+            }
+
+            parent
+                .spawn(Node {
+                    margin: UiRect::top(Val::Px(12.0)),
+                    column_gap: Val::Px(12.0),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for button in [DifficultyButton::Preset, DifficultyButton::Dynamic] {
+                        parent.spawn((
+                            Button,
+                            button,
+                            Node {
+                                padding: UiRect::all(Val::Px(8.0)),
+                                border: UiRect::all(Val::Px(2.0)),
+                                ..default()
+                            },
+                            BorderColor(Color::srgba(1.0, 1.0, 1.0, 0.25)),
+                            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+                            Text::new(button.label(&difficulty)),
+                            TextFont::from_font_size(16.0),
+                        ));
+                    }
+                });
+        });
+}
+
+fn select_with_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut mode: ResMut<GameMode>,
+    mut screen: ResMut<NextState<Screen>>,
+) {
+    for (selected, keys_for_mode) in GameMode::ALL.into_iter().zip(SELECT_KEYS) {
+        if keys.any_just_pressed(keys_for_mode) {
+            *mode = selected;
+            screen.set(Screen::Reset);
+        }
+    }
+
+    // start the previously selected mode
+    if keys.just_pressed(KeyCode::Enter) {
+        screen.set(Screen::Reset);
+    }
+}
+
+fn select_with_buttons(
+    buttons: Query<(&Interaction, &ModeButton), Changed<Interaction>>,
+    mut mode: ResMut<GameMode>,
+    mut screen: ResMut<NextState<Screen>>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            *mode = button.0;
+            screen.set(Screen::Reset);
+        }
+    }
+}
+
+fn toggle_difficulty(selection: &mut DifficultySelection, button: DifficultyButton) {
+    match button {
+        DifficultyButton::Preset => selection.preset = selection.preset.next(),
+        DifficultyButton::Dynamic => selection.dynamic = !selection.dynamic,
+    }
+}
+
+fn difficulty_with_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut selection: ResMut<DifficultySelection>,
+) {
+    if keys.just_pressed(KeyCode::KeyD) {
+        toggle_difficulty(&mut selection, DifficultyButton::Preset);
+    }
+
+    if keys.just_pressed(KeyCode::KeyA) {
+        toggle_difficulty(&mut selection, DifficultyButton::Dynamic);
+    }
+}
+
+fn difficulty_with_buttons(
+    buttons: Query<(&Interaction, &DifficultyButton), Changed<Interaction>>,
+    mut selection: ResMut<DifficultySelection>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            toggle_difficulty(&mut selection, *button);
+        }
+    }
+}
+
+fn update_difficulty_labels(
+    selection: Res<DifficultySelection>,
+    mut buttons: Query<(&DifficultyButton, &mut Text)>,
+) {
+    if !selection.is_changed() {
+        return;
+    }
+
+    for (button, mut text) in &mut buttons {
+        text.0 = button.label(&selection);
+    }
+}
+
+fn return_to_menu(keys: Res<ButtonInput<KeyCode>>, mut screen: ResMut<NextState<Screen>>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        screen.set(Screen::Menu);
+    }
+}